@@ -0,0 +1,17 @@
+"use strict";
+
+// Lets the game keep saves in the browser's localStorage. See src/storage.rs
+function storage_register_plugin(importObject) {
+    importObject.env.storage_get = function (key) {
+        return js_object(window.localStorage.getItem(get_js_object(key)));
+    }
+
+    importObject.env.storage_set = function (key, value) {
+        window.localStorage.setItem(get_js_object(key), get_js_object(value));
+    }
+
+    importObject.env.storage_remove = function (key) {
+        window.localStorage.removeItem(get_js_object(key));
+    }
+}
+miniquad_add_plugin({ register_plugin: storage_register_plugin, version: "0.1.0", name: "storage" });
@@ -0,0 +1,163 @@
+//! Build the settings console from the settings themselves.
+//!
+//! `#[derive(Console)]` on a struct with named fields adds an
+//! `egui(&mut self, ui)` method that shows every field in the console, so
+//! adding a tunable is just adding a field. Each field can be tweaked with a
+//! `#[console(...)]` attribute:
+//!
+//! - `label = "..."`: what to call it, defaults to the field name
+//! - `min = ..`, `max = ..`: the range it is clamped to, defaults to no limit
+//! - `step = ..`: how much it changes as you drag it, defaults to 1
+//! - `tooltip = "..."`: shown when hovering over it
+//! - `group = "..."`: put it in a collapsible group with the same name
+//! - `skip`: leave it out of the console
+//!
+//! The generated code only exists with the `console` feature, and uses the
+//! `Config` trait and `Tweak` struct from `crate::settings::ui` to show each
+//! field.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, Lit, Meta, NestedMeta,
+    Result,
+};
+
+#[proc_macro_derive(Console, attributes(console))]
+pub fn derive_console(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Everything in a field's `#[console(...)]` attribute
+#[derive(Default)]
+struct FieldAttrs {
+    label: Option<String>,
+    min: Option<f64>,
+    max: Option<f64>,
+    step: Option<f64>,
+    tooltip: Option<String>,
+    group: Option<String>,
+    skip: bool,
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new(input.span(), "Console needs named fields")),
+        },
+        _ => return Err(Error::new(input.span(), "Console only works on structs")),
+    };
+    // Ungrouped fields go where they are, grouped fields go where the first
+    // field of their group is.
+    let mut items: Vec<(Option<String>, Vec<TokenStream2>)> = Vec::new();
+    for field in fields {
+        let attrs = parse_attrs(&field.attrs)?;
+        if attrs.skip {
+            continue;
+        }
+        let group = attrs.group;
+        let ident = field.ident.as_ref().expect("named field");
+        let label = attrs.label.unwrap_or_else(|| ident.to_string());
+        let tooltip = option_tokens(attrs.tooltip);
+        let min = option_tokens(attrs.min);
+        let max = option_tokens(attrs.max);
+        let step = attrs.step.unwrap_or(1.);
+        let declare = quote! {
+            self.#ident.declare_ui(
+                &Tweak {
+                    label: #label,
+                    tooltip: #tooltip,
+                    min: #min,
+                    max: #max,
+                    step: #step,
+                },
+                ui,
+            );
+        };
+        match items
+            .iter_mut()
+            .find(|(other, _)| other.is_some() && *other == group)
+        {
+            Some((_, declares)) => declares.push(declare),
+            None => items.push((group, vec![declare])),
+        }
+    }
+    let items = items.into_iter().map(|(group, declares)| match group {
+        Some(group) => quote! {
+            ui.collapsing(#group, |ui| {
+                #(#declares)*
+            });
+        },
+        None => quote! { #(#declares)* },
+    });
+    Ok(quote! {
+        #[cfg(feature = "console")]
+        impl #name {
+            pub fn egui(&mut self, ui: &mut ::egui_macroquad::egui::Ui) {
+                use crate::settings::ui::{Config as _, Tweak};
+                #(#items)*
+            }
+        }
+    })
+}
+
+fn option_tokens<T: quote::ToTokens>(value: Option<T>) -> TokenStream2 {
+    match value {
+        Some(value) => quote! { Some(#value) },
+        None => quote! { None },
+    }
+}
+
+fn parse_attrs(attrs: &[syn::Attribute]) -> Result<FieldAttrs> {
+    let mut res = FieldAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("console")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(Error::new(meta.span(), "expected #[console(...)]")),
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => res.skip = true,
+                NestedMeta::Meta(Meta::NameValue(nv)) => {
+                    let key = nv
+                        .path
+                        .get_ident()
+                        .map(|ident| ident.to_string())
+                        .unwrap_or_default();
+                    match key.as_str() {
+                        "label" => res.label = Some(lit_str(&nv.lit)?),
+                        "tooltip" => res.tooltip = Some(lit_str(&nv.lit)?),
+                        "group" => res.group = Some(lit_str(&nv.lit)?),
+                        "min" => res.min = Some(lit_f64(&nv.lit)?),
+                        "max" => res.max = Some(lit_f64(&nv.lit)?),
+                        "step" => res.step = Some(lit_f64(&nv.lit)?),
+                        _ => return Err(Error::new(nv.path.span(), "unknown console setting")),
+                    }
+                }
+                nested => return Err(Error::new(nested.span(), "unknown console setting")),
+            }
+        }
+    }
+    Ok(res)
+}
+
+fn lit_str(lit: &Lit) -> Result<String> {
+    match lit {
+        Lit::Str(lit) => Ok(lit.value()),
+        _ => Err(Error::new(lit.span(), "expected a string")),
+    }
+}
+
+fn lit_f64(lit: &Lit) -> Result<f64> {
+    match lit {
+        Lit::Int(lit) => lit.base10_parse(),
+        Lit::Float(lit) => lit.base10_parse(),
+        _ => Err(Error::new(lit.span(), "expected a number")),
+    }
+}
@@ -2,72 +2,782 @@
 //!
 //! Keeping track of everyone. Lets make sure everyone gets thier cues.
 
-use crate::{camera::Camera, prelude::*};
-use legion::{system, Resources, Schedule, World};
+use crate::{
+    bee::{Bee, Waypoints},
+    birds::Bird,
+    camera::Camera,
+    enemies::Enemy,
+    flytrap::Flytrap,
+    meadow::{Flower, Meadow, Pickings},
+    power_ups::Effects,
+    prelude::*,
+    recording::Recording,
+    rivals::Rival,
+    save::SaveGame,
+    species::Flora,
+    web::Web,
+};
+use legion::{EntityStore as _, IntoQuery as _};
 
 pub struct StageManager {
     world: World,
-    schedule: Schedule,
+    /// The game logic. Runs the same with or without a window.
+    simulation: Schedule,
+    /// Everything that needs a window: the clock, the inputs and the drawing.
+    /// Missing when the show is headless.
+    stagecraft: Option<Stagecraft>,
+    /// Where the inputs for each tick come from.
+    prompter: Prompter,
     pub resources: Resources,
 }
 
+/// Where the inputs for each tick come from, and where they go.
+enum Prompter {
+    /// From the player, or whoever is stepping the stage, and written down as
+    /// we go if anyone asked for a recording.
+    Live(Option<Recording>),
+    /// Read back from an earlier recording.
+    Replay(std::iter::Peekable<std::vec::IntoIter<Inputs>>),
+}
+
+/// The schedules that surround the simulation when we have a window.
+struct Stagecraft {
+    /// Read the clock and the inputs, before the simulation.
+    cues: Schedule,
+    /// Draw the meadow, after the simulation. Once for each camera.
+    drawing: Schedule,
+    /// Draw over the whole screen, after the meadow.
+    overlay: Schedule,
+    /// A camera for each player, in order. Each takes its turn as the
+    /// [`Camera`] resource.
+    cameras: Vec<Camera>,
+}
+
 impl StageManager {
     pub fn new(settings: Settings) -> StageManager {
-        let mut world = World::default();
-        let mut builder = Schedule::builder();
-        let mut resources = Resources::default();
-        // First, settings
-        resources.insert(settings);
-        // Next timekeeping. Add the clock and the system to keep it current.
-        resources.insert(GameClock {
-            time: get_time(),
-            tick: Duration::default(),
+        let mut stage_manager = Self::headless(settings);
+        let resources = &mut stage_manager.resources;
+        // Keep the clock in step with the wall clock.
+        resources.insert(Metronome {
+            wall_time: get_time(),
+            lag: Duration::default(),
         });
-        builder.add_system(tick_system());
-        // Next inputs.
-        resources.insert(Inputs { mouse_click: None });
-        builder.add_system(inputs_system());
+        // Start at the title
+        resources.insert(GameState::Title);
+        let mut cues = Schedule::builder();
+        cues.add_system(tick_system());
+        crate::controls::cue_call(&mut cues, resources);
+        crate::director::cue_call(&mut cues, resources);
         // Now the costumes
         resources.insert(Texture2D::from_file_with_format(
             crate::spritesheet::SPRITESHEET_PNG_BYTES,
             Some(ImageFormat::Png),
         ));
-        // In Z-order so drawing happens correctly
+        // Point the camera, then the rest in Z-order so drawing happens
+        // correctly
+        let mut drawing = Schedule::builder();
+        crate::camera::draw_call(&mut drawing, resources);
+        crate::meadow::draw_call(&mut drawing, resources);
+        crate::hive::draw_call(&mut drawing, resources);
+        crate::web::draw_call(&mut drawing, resources);
+        crate::flytrap::draw_call(&mut drawing, resources);
+        crate::bee::draw_call(&mut drawing, resources);
+        crate::birds::draw_call(&mut drawing, resources);
+        let mut overlay = Schedule::builder();
+        crate::director::draw_call(&mut overlay, resources);
+        let players = resources
+            .get::<Settings>()
+            .expect("missing settings")
+            .num_players();
+        stage_manager.stagecraft = Some(Stagecraft {
+            cues: cues.build(),
+            drawing: drawing.build(),
+            overlay: overlay.build(),
+            cameras: (0..players)
+                .map(|player| Camera::new(player, players))
+                .collect(),
+        });
+        stage_manager
+    }
+
+    /// Just the simulation: no window, no textures, no drawing. The clock and
+    /// the inputs only change when you [`step`](Self::step) it.
+    pub fn headless(settings: Settings) -> StageManager {
+        let mut world = World::default();
+        let mut builder = Schedule::builder();
+        let mut resources = Resources::default();
+        // First, settings, and the dice they are loaded with
+        resources.insert(Rng::new(settings.seed));
+        resources.insert(settings);
+        // And what grows in the meadow
+        resources.insert(Flora::load());
+        // Next timekeeping and inputs.
+        resources.insert(GameClock::default());
+        resources.insert(Inputs::default());
+        // Without a window there is no title to sit through
+        resources.insert(GameState::Playing);
+        // Then the cast
         crate::meadow::roll_call(&mut world, &mut builder, &mut resources);
+        crate::wind::roll_call(&mut world, &mut builder, &mut resources);
+        crate::web::roll_call(&mut world, &mut builder, &mut resources);
         crate::bee::roll_call(&mut world, &mut builder, &mut resources);
-        crate::camera::roll_call(&mut world, &mut builder, &mut resources);
-        let schedule = builder.build();
+        crate::rivals::roll_call(&mut world, &mut builder, &mut resources);
+        crate::hive::roll_call(&mut world, &mut builder, &mut resources);
+        crate::flytrap::roll_call(&mut world, &mut builder, &mut resources);
+        crate::birds::roll_call(&mut world, &mut builder, &mut resources);
+        crate::enemies::roll_call(&mut world, &mut builder, &mut resources);
+        crate::director::roll_call(&mut world, &mut builder, &mut resources);
+        let simulation = builder.build();
         StageManager {
             world,
-            schedule,
+            simulation,
+            stagecraft: None,
+            prompter: Prompter::Live(None),
             resources,
         }
     }
 
+    /// Pick up a saved game where it left off, paused so the player can get
+    /// their bearings.
+    pub fn resume(save: &SaveGame) -> StageManager {
+        let mut stage_manager = Self::new(save.settings.clone());
+        stage_manager.restore(save);
+        stage_manager.set_state(GameState::Paused);
+        stage_manager
+    }
+
+    /// Replace everyone on stage with the cast of a saved game.
+    fn restore(&mut self, save: &SaveGame) {
+        self.world.clear();
+        let meadow = Meadow::plant(
+            &mut self.world,
+            &save.settings,
+            save.meadow_size,
+            save.grounds.clone(),
+            save.flowers.clone(),
+            save.webs.clone(),
+            save.picked.clone(),
+        );
+        self.resources.insert(meadow);
+        self.resources.insert(Pickings::default());
+        self.world.extend(save.flytraps.iter().copied());
+        for (bird, enemy, pos, vel) in save.birds.iter().copied() {
+            crate::birds::spawn(&mut self.world, bird, enemy, pos, vel);
+        }
+        for (bee, pos, vel, waypoints, effects, player, rival) in save.bees.iter().cloned() {
+            let entity = crate::bee::spawn(&mut self.world, bee, pos, vel, waypoints);
+            let mut entry = self.world.entry(entity).expect("Bee missing");
+            entry.add_component(effects);
+            if let Some(player) = player {
+                entry.add_component(player);
+            }
+            if let Some(rival) = rival {
+                entry.add_component(rival);
+            }
+        }
+        self.resources.insert(GameClock {
+            alpha: 0.,
+            ..save.clock
+        });
+        // A recording only plays back from the start of a round, so there
+        // is no use in one that starts part way through.
+        self.prompter = Prompter::Live(None);
+    }
+
+    /// Everything we need to pick up this game later
+    pub fn save_game(&self) -> SaveGame {
+        let mut save = {
+            let meadow = self.resources.get::<Meadow>().expect("missing meadow");
+            SaveGame::new(
+                self.settings().clone(),
+                *self.resources.get::<GameClock>().expect("missing clock"),
+                meadow.size,
+                meadow.grounds(),
+            )
+        };
+        save.bees = <(
+            &Bee,
+            &Position,
+            &Velocity,
+            &Waypoints,
+            &Effects,
+            Option<&Player>,
+            Option<&Rival>,
+        )>::query()
+        .iter(&self.world)
+        .map(|(bee, pos, vel, waypoints, effects, player, rival)| {
+            (
+                *bee,
+                *pos,
+                *vel,
+                waypoints.clone(),
+                *effects,
+                player.copied(),
+                rival.copied(),
+            )
+        })
+        .collect();
+        let meadow = self.resources.get::<Meadow>().expect("missing meadow");
+        save.flowers = meadow
+            .flower_entities()
+            .map(|(key, entity)| {
+                let entry = self.world.entry_ref(entity).expect("flower missing");
+                (
+                    key,
+                    *entry
+                        .get_component::<Flower>()
+                        .expect("flower missing flower"),
+                    *entry
+                        .get_component::<Position>()
+                        .expect("flower missing pos"),
+                )
+            })
+            .collect();
+        save.flytraps = <(&Flytrap, &Position)>::query()
+            .iter(&self.world)
+            .map(|(flytrap, pos)| (*flytrap, *pos))
+            .collect();
+        save.webs = meadow
+            .web_entities()
+            .map(|(key, entity)| {
+                let entry = self.world.entry_ref(entity).expect("web missing");
+                (
+                    key,
+                    *entry.get_component::<Web>().expect("web missing web"),
+                    *entry.get_component::<Position>().expect("web missing pos"),
+                )
+            })
+            .collect();
+        save.picked = meadow.picked();
+        save.birds = <(&Bird, &Enemy, &Position, &Velocity)>::query()
+            .iter(&self.world)
+            .map(|(bird, enemy, pos, vel)| (*bird, *enemy, *pos, *vel))
+            .collect();
+        save
+    }
+
     pub fn execute(&mut self) {
-        self.schedule.execute(&mut self.world, &mut self.resources)
+        let mut stagecraft = self
+            .stagecraft
+            .take()
+            .expect("headless stage cannot execute, step it instead");
+        // The controls see through the first player's camera
+        self.film(&mut stagecraft.cues, &mut stagecraft.cameras[0]);
+        // Run as many fixed ticks as the wall clock has gotten ahead of us,
+        // but only while the round is on. A tick can end it.
+        let tick = self.settings().tick();
+        if !self.playing() {
+            self.resources
+                .get_mut::<Metronome>()
+                .expect("missing metronome")
+                .lag = Duration::default();
+        }
+        while self.playing()
+            && self
+                .resources
+                .get_mut::<Metronome>()
+                .expect("missing metronome")
+                .beat(tick)
+        {
+            self.simulate(tick);
+        }
+        let alpha = self
+            .resources
+            .get::<Metronome>()
+            .expect("missing metronome")
+            .alpha(tick);
+        self.resources
+            .get_mut::<GameClock>()
+            .expect("missing clock")
+            .alpha = alpha;
+        // Once for the whole window, since each camera only draws its own part
+        clear_background(self.settings().meadow_color);
+        for camera in &mut stagecraft.cameras {
+            self.film(&mut stagecraft.drawing, camera);
+        }
+        self.film(&mut stagecraft.overlay, &mut stagecraft.cameras[0]);
+        crate::camera::full_screen();
+        self.stagecraft = Some(stagecraft);
+    }
+
+    /// Run `schedule` with `camera` as the [`Camera`] resource.
+    fn film(&mut self, schedule: &mut Schedule, camera: &mut Camera) {
+        self.resources.insert(*camera);
+        schedule.execute(&mut self.world, &mut self.resources);
+        *camera = self.resources.remove::<Camera>().expect("missing camera");
     }
 
-    pub fn settings(&self) -> atomic_refcell::AtomicRefMut<Settings> {
+    /// Run the simulation for up to `ticks` ticks, with `inputs` held the
+    /// whole time. Stops early if the round stops.
+    #[cfg(test)]
+    pub fn step(&mut self, ticks: usize, inputs: Inputs) {
+        self.resources.insert(inputs);
+        let tick = self.settings().tick();
+        for _ in 0..ticks {
+            if !self.playing() {
+                break;
+            }
+            self.simulate(tick);
+        }
+    }
+
+    /// Whether the round is on, and so whether the simulation should tick
+    fn playing(&self) -> bool {
+        self.state() == GameState::Playing
+    }
+
+    pub fn state(&self) -> GameState {
+        *self
+            .resources
+            .get::<GameState>()
+            .expect("missing game state")
+    }
+
+    pub fn set_state(&mut self, state: GameState) {
+        self.resources.insert(state);
+    }
+
+    /// Write down the inputs for every tick from now on. Only a recording
+    /// from the start of a round plays back.
+    pub fn start_recording(&mut self) {
+        let recording = Recording::new(&self.settings());
+        self.prompter = Prompter::Live(Some(recording));
+    }
+
+    /// Take the inputs for every tick from `recording` instead. The stage
+    /// should have been set with the recording's settings.
+    pub fn play_back(&mut self, recording: &Recording) {
+        let inputs: Vec<_> = recording.inputs().collect();
+        self.prompter = Prompter::Replay(inputs.into_iter().peekable());
+    }
+
+    /// Has every tick of the recording we are playing back been played?
+    pub fn replay_finished(&mut self) -> bool {
+        match &mut self.prompter {
+            Prompter::Live(_) => false,
+            Prompter::Replay(inputs) => inputs.peek().is_none(),
+        }
+    }
+
+    /// Everything that happened since we started recording, if we did.
+    pub fn recording(&self) -> Option<&Recording> {
+        match &self.prompter {
+            Prompter::Live(recording) => recording.as_ref(),
+            Prompter::Replay(_) => None,
+        }
+    }
+
+    /// The first player's score
+    pub fn score(&self) -> u32 {
+        <(&Player, &Bee)>::query()
+            .iter(&self.world)
+            .find(|(player, _)| player.0 == 0)
+            .expect("Player's bee missing")
+            .1
+            .score()
+    }
+
+    /// A single fixed tick of the simulation.
+    fn simulate(&mut self, tick: Duration) {
+        match &mut self.prompter {
+            Prompter::Live(Some(recording)) => {
+                recording.record(&self.resources.get::<Inputs>().expect("missing inputs"))
+            }
+            Prompter::Live(None) => {}
+            Prompter::Replay(inputs) => match inputs.next() {
+                Some(inputs) => self.resources.insert(inputs),
+                // The recording is over, nothing more happens
+                None => return,
+            },
+        }
+        self.resources
+            .get_mut::<GameClock>()
+            .expect("missing clock")
+            .advance(tick);
+        self.simulation
+            .execute(&mut self.world, &mut self.resources);
+        // Waypoints are queued once, not every tick until the next frame
+        if let Prompter::Live(_) = self.prompter {
+            let mut inputs = self.resources.get_mut::<Inputs>().expect("missing inputs");
+            for inputs in &mut inputs.players {
+                inputs.waypoint = None;
+            }
+        }
+    }
+
+    pub fn settings(&self) -> atomic_refcell::AtomicRefMut<'_, Settings> {
         self.resources
             .get_mut::<Settings>()
             .expect("missing settings")
     }
 }
 
+/// Keeps the beat for the fixed simulation ticks. Loaded as a resource when
+/// we have a window.
+struct Metronome {
+    /// The wall clock at the last frame
+    wall_time: f64,
+    /// How far the wall clock is ahead of the game clock
+    lag: Duration,
+}
+impl Metronome {
+    /// Use up a `tick` of the lag, if there is that much left.
+    fn beat(&mut self, tick: Duration) -> bool {
+        let beat = self.lag >= tick;
+        if beat {
+            self.lag -= tick;
+        }
+        beat
+    }
+
+    /// How far the lag has made it to the next `tick`
+    fn alpha(&self, tick: Duration) -> f32 {
+        self.lag.as_secs_f32() / tick.as_secs_f32()
+    }
+}
+
 #[system]
-fn tick(#[resource] clock: &mut GameClock) {
+fn tick(#[resource] metronome: &mut Metronome, #[resource] settings: &Settings) {
     let time = get_time();
-    let tick = Duration::from_secs_f64(time - clock.time);
-    clock.time = time;
-    clock.tick = tick;
+    // A long frame (like coming back to a background tab) would teleport
+    // everything, so just drop the excess.
+    let frame = Duration::from_secs_f64(time - metronome.wall_time).min(settings.max_frame_time());
+    metronome.wall_time = time;
+    metronome.lag += frame;
 }
 
-#[system]
-fn inputs(#[resource] inputs: &mut Inputs, #[resource] camera: &Camera) {
-    inputs.mouse_click = if is_mouse_button_down(MouseButton::Left) {
-        Some(camera.screen_to_world(mouse_position().into()))
-    } else {
-        None
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// No wind, so only the inputs move the bees
+    fn still_air() -> Settings {
+        Settings {
+            wind_strength: 0.,
+            ..Settings::default()
+        }
+    }
+
+    /// Still air, and nothing out to get the bees either
+    fn calm_meadow() -> Settings {
+        Settings {
+            num_birds: 0,
+            num_flytraps: 0,
+            num_webs: 0,
+            ..still_air()
+        }
+    }
+
+    fn clock(stage: &StageManager) -> GameClock {
+        *stage.resources.get::<GameClock>().expect("missing clock")
+    }
+
+    fn player_pos(stage: &StageManager, whose: usize) -> Vec2 {
+        let (_, Position(pos)) = <(&Player, &Position)>::query()
+            .iter(&stage.world)
+            .find(|(player, _)| player.0 == whose)
+            .expect("Player's bee missing");
+        *pos
+    }
+
+    #[test]
+    fn headless_steps_the_clock() {
+        let mut stage = StageManager::headless(still_air());
+        stage.step(60, Inputs::default());
+        assert_eq!(stage.state(), GameState::Playing);
+        let expected = stage.settings().tick().as_secs_f64() * 60.;
+        assert!((clock(&stage).time - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn inputs_steer_the_players_bee() {
+        let mut stage = StageManager::headless(calm_meadow());
+        let start = player_pos(&stage, 0);
+        let mut inputs = Inputs::default();
+        inputs.players[0].steering = Some(Steering::Thrust(Vec2::X));
+        stage.step(60, inputs);
+        let moved = player_pos(&stage, 0) - start;
+        assert!(moved.x > 10., "bee only moved {:?}", moved);
+        assert!(moved.y.abs() < moved.x);
+    }
+
+    #[test]
+    fn each_player_steers_their_own_bee() {
+        let mut stage = StageManager::headless(Settings {
+            num_players: 2,
+            ..calm_meadow()
+        });
+        let start = [player_pos(&stage, 0), player_pos(&stage, 1)];
+        let mut inputs = Inputs::default();
+        inputs.players[0].steering = Some(Steering::Thrust(-Vec2::X));
+        inputs.players[1].steering = Some(Steering::Thrust(Vec2::X));
+        stage.step(60, inputs);
+        assert!(player_pos(&stage, 0).x < start[0].x - 10.);
+        assert!(player_pos(&stage, 1).x > start[1].x + 10.);
+    }
+
+    #[test]
+    fn every_bee_picks_the_flower_it_is_on() {
+        let mut stage = StageManager::headless(Settings {
+            seed: 3,
+            num_players: 2,
+            num_rivals: 3,
+            pollen_capacity: 100,
+            ..calm_meadow()
+        });
+        let flowers: Vec<Vec2> = <(&Flower, &Position)>::query()
+            .iter(&stage.world)
+            .map(|(_, pos)| pos.0)
+            .collect();
+        let bees: Vec<legion::Entity> = <(legion::Entity, &Bee)>::query()
+            .iter(&stage.world)
+            .map(|(entity, _)| *entity)
+            .collect();
+        assert_eq!(bees.len(), 5);
+        for (&bee, &flower) in bees.iter().zip(flowers.iter().step_by(10)) {
+            *stage
+                .world
+                .entry_mut(bee)
+                .expect("bee missing")
+                .get_component_mut::<Position>()
+                .expect("bee missing pos") = Position::from(flower);
+        }
+        stage.step(1, Inputs::default());
+        for bee in bees {
+            let entry = stage.world.entry_ref(bee).expect("bee missing");
+            let bee = entry.get_component::<Bee>().expect("bee missing bee data");
+            assert!(bee.pollen() > 0, "a bee missed its flower");
+        }
+    }
+
+    #[test]
+    fn same_seed_grows_the_same_meadow() {
+        let settings = Settings {
+            seed: 1234,
+            ..still_air()
+        };
+        let first = StageManager::headless(settings.clone()).save_game();
+        let second = StageManager::headless(settings).save_game();
+        assert!(!first.flowers.is_empty());
+        assert_eq!(first.flowers, second.flowers);
+        assert_eq!(first.webs, second.webs);
+    }
+
+    #[test]
+    fn another_seed_grows_another_meadow() {
+        let first = StageManager::headless(Settings {
+            seed: 1234,
+            ..still_air()
+        });
+        let second = StageManager::headless(Settings {
+            seed: 4321,
+            ..still_air()
+        });
+        assert_ne!(first.save_game().flowers, second.save_game().flowers);
+    }
+
+    #[test]
+    fn restoring_a_save_plants_the_same_meadow() {
+        let mut stage = StageManager::headless(Settings {
+            seed: 5,
+            infinite_meadow: true,
+            num_birds: 0,
+            num_flytraps: 0,
+            ..still_air()
+        });
+        stage.step(1, Inputs::default());
+        let (_, flower) = {
+            let meadow = stage.resources.get::<Meadow>().expect("missing meadow");
+            let flower = meadow.flower_entities().nth(7).expect("too few flowers");
+            flower
+        };
+        let mut entry = stage.world.entry(flower).expect("flower missing");
+        entry
+            .get_component_mut::<Flower>()
+            .expect("flower missing flower data")
+            .collected = true;
+        let save = stage.save_game();
+        assert!(save.grounds.len() > 1);
+        assert!(!save.webs.is_empty());
+
+        let mut restored = StageManager::headless(still_air());
+        restored.restore(&save);
+        let again = restored.save_game();
+        assert_eq!(again.grounds, save.grounds);
+        assert_eq!(again.flowers, save.flowers);
+        assert_eq!(again.webs, save.webs);
+        assert_eq!(again.picked, save.picked);
+        assert_eq!(
+            again
+                .flowers
+                .iter()
+                .filter(|(_, flower, _)| flower.collected)
+                .count(),
+            1
+        );
+    }
+
+    /// Fly the player's bee to the nearest flower it has room for, or home
+    /// once there is none
+    fn autopilot(stage: &StageManager) -> Inputs {
+        let meadow = stage.resources.get::<Meadow>().expect("missing meadow");
+        let (_, bee, &Position(pos)) = <(&Player, &Bee, &Position)>::query()
+            .iter(&stage.world)
+            .find(|(player, ..)| player.0 == 0)
+            .expect("Player's bee missing");
+        let position = |entity| {
+            *stage
+                .world
+                .entry_ref(entity)
+                .expect("entity missing")
+                .get_component::<Position>()
+                .expect("position missing")
+        };
+        let flora = stage.resources.get::<Flora>().expect("missing flora");
+        let room = stage.settings().pollen_capacity as u32 - bee.pollen();
+        let flower = meadow.nearest_flower(pos, |flower| {
+            let entry = stage.world.entry_ref(flower).expect("flower missing");
+            let flower = entry
+                .get_component::<Flower>()
+                .expect("flower missing flower data");
+            !flower.collected && flora.get(flower.species).nectar <= room
+        });
+        let destination = match flower {
+            Some(flower) => position(flower),
+            None => position(meadow.hive),
+        };
+        let mut inputs = Inputs::default();
+        inputs.players[0].steering = Some(Steering::Destination(destination.0));
+        inputs
+    }
+
+    #[test]
+    fn replay_scores_the_same_as_the_recording() {
+        let mut stage = StageManager::headless(Settings {
+            seed: 99,
+            pollen_capacity: 3,
+            num_rivals: 0,
+            ..calm_meadow()
+        });
+        assert!(stage.recording().is_none(), "recording without being asked");
+        stage.start_recording();
+        for _ in 0..60 {
+            let inputs = autopilot(&stage);
+            stage.step(30, inputs);
+        }
+        let score = stage.score();
+        assert!(score > 0, "the autopilot never made it home");
+        let path = std::env::temp_dir().join("bumble-umble-gee-replay-test.rec");
+        stage
+            .recording()
+            .expect("nothing recorded")
+            .save(&path)
+            .expect("could not save the recording");
+        let recording = Recording::load(&path).expect("could not load the recording");
+        std::fs::remove_file(&path).expect("could not clean up the recording");
+
+        let mut replay = StageManager::headless(recording.settings.clone());
+        replay.play_back(&recording);
+        while !replay.replay_finished() {
+            replay.step(1, Inputs::default());
+        }
+        assert_eq!(replay.score(), score);
+    }
+
+    fn player_bee(stage: &StageManager) -> Bee {
+        let (_, bee) = <(&Player, &Bee)>::query()
+            .iter(&stage.world)
+            .find(|(player, _)| player.0 == 0)
+            .expect("Player's bee missing");
+        *bee
+    }
+
+    #[test]
+    fn exhausted_bees_walk_to_a_flower_for_nectar() {
+        let mut stage = StageManager::headless(Settings {
+            energy_drain: 100.,
+            nectar_energy: 30.,
+            ..calm_meadow()
+        });
+        let mut thrust = Inputs::default();
+        thrust.players[0].steering = Some(Steering::Thrust(Vec2::X));
+        for _ in 0..600 {
+            stage.step(1, thrust);
+            if player_bee(&stage).is_exhausted() {
+                break;
+            }
+        }
+        assert!(player_bee(&stage).is_exhausted());
+        // Nothing the player does gets it going again, only a drink
+        for _ in 0..3600 {
+            stage.step(1, thrust);
+            if !player_bee(&stage).is_exhausted() {
+                break;
+            }
+        }
+        let bee = player_bee(&stage);
+        assert!(!bee.is_exhausted(), "the bee never found any nectar");
+        assert!(bee.is_landed());
+    }
+
+    #[test]
+    fn rivals_go_foraging() {
+        let mut stage = StageManager::headless(Settings {
+            seed: 3,
+            num_rivals: 2,
+            energy_drain: 0.,
+            ..calm_meadow()
+        });
+        stage.step(60 * 30, Inputs::default());
+        let rivals: Vec<Bee> = <(&Rival, &Bee)>::query()
+            .iter(&stage.world)
+            .map(|(_, bee)| *bee)
+            .collect();
+        assert_eq!(rivals.len(), 2);
+        for bee in rivals {
+            assert!(bee.pollen() + bee.score() > 0, "a rival picked nothing");
+        }
+    }
+
+    #[test]
+    fn rivals_bank_the_last_of_the_pollen() {
+        let mut stage = StageManager::headless(Settings {
+            seed: 7,
+            endless: true,
+            num_flowers: 1,
+            energy_drain: 0.,
+            ..calm_meadow()
+        });
+        // Only picking the meadow clean ends an endless round
+        stage.step(60 * 60 * 10, Inputs::default());
+        assert_eq!(stage.state(), GameState::Results);
+    }
+
+    #[test]
+    fn flowers_worth_more_than_the_room_are_left() {
+        let mut stage = StageManager::headless(Settings {
+            pollen_capacity: 1,
+            energy_drain: 0.,
+            ..calm_meadow()
+        });
+        stage.step(60 * 60, Inputs::default());
+        let flora = Flora::load();
+        let picked: Vec<u32> = stage
+            .save_game()
+            .flowers
+            .iter()
+            .filter(|(_, flower, _)| flower.collected)
+            .map(|(_, flower, _)| flora.get(flower.species).nectar)
+            .collect();
+        assert!(!picked.is_empty(), "nobody picked anything");
+        assert!(picked.iter().all(|&nectar| nectar <= 1), "{:?}", picked);
+    }
+
+    #[test]
+    fn step_stops_when_the_round_does() {
+        let mut stage = StageManager::headless(still_air());
+        stage.set_state(GameState::Results);
+        stage.step(60, Inputs::default());
+        assert_eq!(clock(&stage).time, 0.);
+    }
 }
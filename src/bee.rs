@@ -3,16 +3,20 @@
 //! Follow the users touch! Mine the flowers! Do your best little bee! We are
 //! rooting for you!
 
-use std::ops::Rem;
+use std::{collections::VecDeque, ops::Rem};
 
 use crate::{
-    meadow::{Flower, Meadow},
+    meadow::{Flower, Meadow, Pickings},
+    power_ups::Effects,
     prelude::*,
+    species::Flora,
     spritesheet,
+    wind::Wind,
 };
-use legion::{system, world::SubWorld, EntityStore};
+use legion::{system, world::SubWorld, Entity, EntityStore as _, IntoQuery as _};
 use macroquad::prelude::*;
-use parry2d::{math::Isometry, query::intersection_test, shape::Ball};
+use parry2d::{math::Isometry, query::distance, shape::Ball};
+use serde::{Deserialize, Serialize};
 
 /// This is the bees sprite rect translated so the bee position is at the
 /// origin.
@@ -32,161 +36,570 @@ const BEE_HITBOX: Rect = Rect {
     h: BEE_SPRITE.h / 3.0,
 };
 
+/// The walking sprite, lined up the same way as [`BEE_SPRITE`]
+const BEE_WALKING_SPRITE: Rect = Rect {
+    x: -(spritesheet::BEE_WALKING_FRAME_SIZE.x as f32 * 0.85),
+    y: -(spritesheet::BEE_WALKING_FRAME_SIZE.y as f32 * 0.5),
+    w: spritesheet::BEE_WALKING_FRAME_SIZE.x as f32,
+    h: spritesheet::BEE_WALKING_FRAME_SIZE.y as f32,
+};
+
+/// Below this much of the max thrust, and this slow, the bee settles down
+const LANDING_THRUST: f32 = 0.05;
+const LANDING_SPEED: f32 = 10.;
+/// Above this much of the max thrust the bee takes off again
+const TAKE_OFF_THRUST: f32 = 0.1;
+/// How much energy an exhausted bee needs before it can take off again
+const TAKE_OFF_ENERGY: f32 = 0.25;
+/// How fast an exhausted bee walks to a flower
+const WALKING_SPEED: f32 = 40.;
+
+/// Bees nobody is flying are a bit darker, so the players can find their own
+const WILD_BEE_TINT: Color = Color::new(0.6, 0.6, 0.6, 1.);
+
+/// How close the bee has to get to where it is going before it heads for the
+/// next waypoint
+const ARRIVED: f32 = 10.;
+
+/// Whether the bee is in the air
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+enum Flight {
+    Flying,
+    /// Landed, and free to take off whenever
+    Resting,
+    /// Out of energy, and walking to the nearest flower to drink until it
+    /// gets some back
+    Exhausted,
+}
+
 /// The bees stats
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Bee {
     destination: Vec2,
     thrust: Vec2,
+    /// Which way the bee is facing, as a unit vector
+    heading: Vec2,
+    /// Pollen banked at the hive
     score: u32,
+    /// Pollen being carried back to the hive
+    pollen: u32,
+    /// How much of [`Settings::max_thrust`] the bee can put out
+    strength: f32,
+    /// How much the bee has left in it, in `0..=1`
+    energy: f32,
+    flight: Flight,
+    /// Held by a flytrap until the [`GameClock`] gets here
+    trapped_until: f64,
+    /// Caught in a [`Web`](crate::web::Web)
+    snagged: bool,
 }
 impl Bee {
-    pub fn transform_rect(&self, pos: Vec2, settings: &Settings, rect: &Rect) -> Quad {
+    /// A bee headed for `destination`, with `strength` times the usual thrust
+    pub fn new(destination: Vec2, strength: f32) -> Self {
+        Bee {
+            destination,
+            thrust: Vec2::default(),
+            heading: Vec2::X,
+            score: 0,
+            pollen: 0,
+            strength,
+            energy: 1.,
+            flight: Flight::Flying,
+            trapped_until: 0.,
+            snagged: false,
+        }
+    }
+
+    pub fn is_landed(&self) -> bool {
+        self.flight != Flight::Flying
+    }
+
+    /// Too tired to fly, so only nectar will get the bee going again
+    pub fn is_exhausted(&self) -> bool {
+        self.flight == Flight::Exhausted
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn pollen(&self) -> u32 {
+        self.pollen
+    }
+
+    /// Can the bee carry any more pollen?
+    pub fn is_full(&self, settings: &Settings) -> bool {
+        self.pollen as usize >= settings.pollen_capacity
+    }
+
+    /// Lose up to `pollen` of the pollen the bee is carrying
+    pub fn drop_pollen(&mut self, pollen: u32) {
+        self.pollen = self.pollen.saturating_sub(pollen);
+    }
+
+    /// Hold the bee where it is until the [`GameClock`] gets to `until`
+    pub fn trap(&mut self, until: f64) {
+        self.trapped_until = until;
+    }
+
+    pub fn is_trapped(&self, clock: &GameClock) -> bool {
+        clock.time < self.trapped_until
+    }
+
+    pub fn snag(&mut self, snagged: bool) {
+        self.snagged = snagged;
+    }
+
+    pub fn is_snagged(&self) -> bool {
+        self.snagged
+    }
+
+    /// Turn the pollen the bee is carrying into score
+    pub fn bank_pollen(&mut self) {
+        self.score += self.pollen;
+        self.pollen = 0;
+    }
+
+    /// Head for `destination`, unless the bee is too tired to go anywhere
+    /// but the nearest flower
+    pub fn set_destination(&mut self, destination: Vec2) {
+        if !self.is_exhausted() {
+            self.destination = destination;
+        }
+    }
+
+    /// Where a bee at `pos` that is `size` big can touch things
+    pub fn hitbox(&self, pos: Vec2, size: f32) -> Quad {
+        self.transform_rect(pos, size, &BEE_HITBOX)
+    }
+
+    /// Place `rect` on a bee at `pos` that is `size` big (see
+    /// [`Settings::bee_size`])
+    pub fn transform_rect(&self, pos: Vec2, size: f32, rect: &Rect) -> Quad {
         Quad::from_rect(rect)
-            .scale_to_origin(settings.bee_size / 1000.)
-            .rotate_to(self.thrust.normalize())
+            .scale_to_origin(size / 1000.)
+            .rotate_to(self.heading)
             .translate(pos)
     }
 }
 
+/// Where the bee will head next, in order, once it gets where it is going
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct Waypoints(VecDeque<Vec2>);
+
 pub fn roll_call(
     world: &mut legion::world::World,
     systems: &mut legion::systems::Builder,
     resources: &mut legion::systems::Resources,
 ) {
-    let entity = {
+    {
         let meadow = resources.get::<Meadow>().expect("No meadow");
-        world.push((
-            Bee {
-                destination: meadow.rand_pos(),
-                thrust: Vec2::default(),
-                score: 0,
-            },
-            Position::from(meadow.rand_pos()),
-            Velocity::default(),
-        ))
-    };
-    resources.insert(TheBee { entity });
+        let settings = resources.get::<Settings>().expect("No settings");
+        let mut rng = resources.get_mut::<Rng>().expect("No rng");
+        for player in 0..settings.num_players() {
+            let destination = meadow.rand_pos(&mut rng);
+            let pos = meadow.rand_pos(&mut rng);
+            let entity = spawn(
+                world,
+                Bee::new(destination, 1.),
+                Position::from(pos),
+                Velocity::default(),
+                Waypoints::default(),
+            );
+            world
+                .entry(entity)
+                .expect("Bee missing")
+                .add_component(Player(player));
+        }
+    }
     systems.add_system(update_destination_system());
+    systems.add_system(seek_nectar_system());
     systems.add_system(head_for_destination_system());
+    systems.add_system(tire_system());
     systems.add_system(fly_system());
     systems.add_system(found_flower_system());
+}
+
+pub fn draw_call(
+    systems: &mut legion::systems::Builder,
+    _resources: &mut legion::systems::Resources,
+) {
+    systems.add_system(draw_route_system());
     systems.add_system(draw_system());
+    systems.add_system(draw_energy_system());
     systems.add_system(draw_score_system());
 }
 
+/// Put a bee in the `world`
+pub fn spawn(
+    world: &mut legion::world::World,
+    bee: Bee,
+    pos: Position,
+    vel: Velocity,
+    waypoints: Waypoints,
+) -> Entity {
+    world.push((
+        bee,
+        pos,
+        PreviousPosition::from(pos.0),
+        vel,
+        waypoints,
+        Effects::default(),
+    ))
+}
+
+/// Only the players' bees listen to the inputs
 #[system(for_each)]
-fn update_destination(bee: &mut Bee, #[resource] inputs: &Inputs, #[resource] meadow: &Meadow) {
-    if let Some(mouse_pos) = inputs.mouse_click {
-        bee.destination = meadow.clamp(mouse_pos);
+fn update_destination(
+    bee: &mut Bee,
+    pos: &Position,
+    waypoints: &mut Waypoints,
+    player: &Player,
+    #[resource] inputs: &Inputs,
+    #[resource] meadow: &Meadow,
+    #[resource] clock: &GameClock,
+) {
+    // Struggling is no use, and neither is flapping without the energy
+    if bee.is_trapped(clock) || bee.is_exhausted() {
+        return;
+    }
+    let inputs = &inputs.players[player.0];
+    if let Some(waypoint) = inputs.waypoint {
+        waypoints.0.push_back(meadow.clamp(waypoint));
+    }
+    match inputs.steering {
+        // Steering by hand throws out the route
+        Some(Steering::Destination(dest)) => {
+            bee.destination = meadow.clamp(dest);
+            waypoints.0.clear();
+        }
+        // Flying by hand, so stay wherever they let go, as long as it is in
+        // the meadow.
+        Some(Steering::Thrust(_)) => {
+            bee.destination = meadow.clamp(pos.0);
+            waypoints.0.clear();
+        }
+        None => {
+            if bee.destination.distance(pos.0) < ARRIVED {
+                if let Some(waypoint) = waypoints.0.pop_front() {
+                    bee.destination = waypoint;
+                }
+            }
+        }
+    }
+}
+
+/// An exhausted bee can not fly, so it walks to the nearest flower with
+/// nectar left to drink
+#[system]
+#[write_component(Bee)]
+#[read_component(Position)]
+#[read_component(Flower)]
+fn seek_nectar(world: &mut SubWorld, #[resource] meadow: &Meadow) {
+    let exhausted: Vec<(Entity, Vec2)> = <(Entity, &Bee, &Position)>::query()
+        .iter(world)
+        .filter(|(_, bee, _)| bee.is_exhausted())
+        .map(|(entity, _, pos)| (*entity, pos.0))
+        .collect();
+    for (entity, pos) in exhausted {
+        let nearest = meadow.nearest_flower(pos, |flower| {
+            !world
+                .entry_ref(flower)
+                .expect("flower disappeared")
+                .get_component::<Flower>()
+                .expect("Flower missing flower data")
+                .collected
+        });
+        if let Some(flower) = nearest {
+            let Position(flower_pos) = *world
+                .entry_ref(flower)
+                .expect("flower disappeared")
+                .get_component::<Position>()
+                .expect("Flower missing pos");
+            world
+                .entry_mut(entity)
+                .expect("Bee missing")
+                .get_component_mut::<Bee>()
+                .expect("Bee missing bee data")
+                .destination = flower_pos;
+        }
     }
 }
 
 #[system(for_each)]
-fn head_for_destination(bee: &mut Bee, pos: &Position, #[resource] settings: &Settings) {
+fn head_for_destination(
+    bee: &mut Bee,
+    pos: &Position,
+    player: Option<&Player>,
+    effects: &Effects,
+    #[resource] inputs: &Inputs,
+    #[resource] clock: &GameClock,
+    #[resource] settings: &Settings,
+) {
     let disp = bee.destination - pos.0;
     let dist = disp.length();
-    bee.thrust = if dist > settings.max_thrust {
-        (disp / dist) * settings.max_thrust
-    } else {
-        disp
+    let max_thrust = settings.max_thrust * bee.strength * effects.thrust_scale(clock);
+    let steering = player.and_then(|player| inputs.players[player.0].steering);
+    bee.thrust = match steering {
+        // Walking, not flying
+        _ if bee.is_exhausted() => Vec2::ZERO,
+        // Only push the bee around while it is inside the meadow, otherwise
+        // it heads back in.
+        Some(Steering::Thrust(push)) if dist == 0. => push.clamp_length_max(1.) * max_thrust,
+        _ if dist > max_thrust => (disp / dist) * max_thrust,
+        _ => disp,
+    }
+}
+
+/// Flying uses up energy, and only nectar brings it back. Land when the bee is
+/// done flying, or has to be.
+#[system(for_each)]
+fn tire(
+    bee: &mut Bee,
+    vel: &Velocity,
+    #[resource] clock: &GameClock,
+    #[resource] settings: &Settings,
+) {
+    let dt = clock.tick.as_secs_f32();
+    let effort = bee.thrust.length() / settings.max_thrust;
+    match bee.flight {
+        Flight::Flying => {
+            bee.energy -= dt * effort * settings.energy_drain / 100.;
+            if bee.thrust != Vec2::ZERO {
+                bee.heading = bee.thrust.normalize();
+            }
+            if bee.energy <= 0. {
+                bee.energy = 0.;
+                bee.flight = Flight::Exhausted;
+            } else if effort < LANDING_THRUST && vel.0.length() < LANDING_SPEED {
+                bee.flight = Flight::Resting;
+            }
+        }
+        Flight::Exhausted => {
+            if vel.0 != Vec2::ZERO {
+                bee.heading = vel.0.normalize();
+            }
+            // Drinking enough on a flower, it can rest there for a bit
+            if bee.energy >= TAKE_OFF_ENERGY {
+                bee.flight = Flight::Resting;
+            }
+        }
+        Flight::Resting => {
+            if effort > TAKE_OFF_THRUST {
+                bee.flight = Flight::Flying;
+            }
+        }
     }
 }
 
 #[system(for_each)]
 fn fly(
     bee: &Bee,
+    pos: &Position,
     vel: &mut Velocity,
+    #[resource] wind: &Wind,
     #[resource] clock: &GameClock,
     #[resource] settings: &Settings,
 ) {
+    if bee.flight == Flight::Resting || bee.is_trapped(clock) {
+        *vel = Velocity::default();
+        return;
+    }
+    if bee.is_exhausted() {
+        let dt = clock.tick.as_secs_f32();
+        *vel = Velocity::from(((bee.destination - pos.0) / dt).clamp_length_max(WALKING_SPEED));
+        return;
+    }
     let Velocity(v) = *vel;
-    // Add in a bit of drag, with a bit of random walk thrown in
-    let wind = -v;
-    let thrust = bee.thrust + wind * settings.wind_resistance / 100.;
-    *vel = Velocity::from(v + clock.tick.as_secs_f32() * thrust / settings.mass);
+    let dt = clock.tick.as_secs_f32();
+    // Drag against the air, which is blowing this way and that, and a lot
+    // more of it in a web. It eases the bee toward the wind however strong
+    // it is, rather than overshooting.
+    let wind = wind.at(pos.0, clock, settings);
+    let resistance = if bee.snagged {
+        settings.wind_resistance + settings.web_drag
+    } else {
+        settings.wind_resistance
+    };
+    let drift = wind + (v - wind) * (-dt * resistance / 100. / settings.mass).exp();
+    *vel = Velocity::from(drift + dt * bee.thrust / settings.mass);
 }
 
+/// Every bee collects whatever flowers it touches, as long as it has room for
+/// the pollen. If two bees reach the same flower on the same tick, the first
+/// one in the world gets it.
 #[system]
 #[write_component(Bee)]
 #[write_component(Flower)]
+#[write_component(Effects)]
 #[read_component(Position)]
 fn found_flower(
     world: &mut SubWorld,
-    #[resource] the_bee: &TheBee,
     #[resource] meadow: &Meadow,
+    #[resource] clock: &GameClock,
     #[resource] settings: &Settings,
+    #[resource] flora: &Flora,
+    #[resource] pickings: &mut Pickings,
 ) {
-    let bee = world.entry_ref(the_bee.entity).expect("Bee missing");
-    let Position(bee_pos) = *bee.get_component::<Position>().expect("Bee missing pos");
-    let bee = *bee.get_component::<Bee>().expect("Bee missing bee data");
-    let hitbox = bee.transform_rect(bee_pos, settings, &BEE_HITBOX);
-    let bee_shape = hitbox.polyline();
+    let bees: Vec<(Entity, Bee, Vec2, Effects)> = <(Entity, &Bee, &Position, &Effects)>::query()
+        .iter(world)
+        .map(|(entity, bee, pos, effects)| (*entity, *bee, pos.0, *effects))
+        .collect();
     let identity = Isometry::identity();
-    let mut score_delta = 0u32;
-    for index in meadow.flower_index_within(hitbox.bb()) {
-        let mut flower_entry = world
-            .entry_mut(meadow.flower_entities[index])
-            .expect("flower disappeared");
-        let Position(flower_pos) = *flower_entry
-            .get_component::<Position>()
-            .expect("Flower missing pos");
-        let flower = flower_entry
-            .get_component_mut::<Flower>()
-            .expect("Flower missing flower data");
-        if flower.collected {
+    for (entity, bee, bee_pos, mut effects) in bees {
+        let room = (settings.pollen_capacity as u32).saturating_sub(bee.pollen);
+        // An exhausted bee drinks the nectar even if it has no room for the
+        // pollen
+        let thirsty = bee.is_exhausted();
+        if room == 0 && !thirsty {
             continue;
         }
-        let flower_shape = Ball::new(flower.radius);
-        let flower_isometry = Isometry::translation(flower_pos.x, flower_pos.y);
-        if intersection_test(&identity, &bee_shape, &flower_isometry, &flower_shape).unwrap() {
-            flower.collected = true;
-            score_delta += 1;
+        let hitbox = bee.hitbox(bee_pos, effects.bee_size(clock, settings));
+        let bee_shape = hitbox.polyline();
+        let reach = effects.magnet_reach(clock);
+        let Rect { x, y, w, h } = hitbox.bb();
+        let reach_box = Rect::new(x - reach, y - reach, w + reach * 2., h + reach * 2.);
+        let mut pollen = 0u32;
+        let mut nectar_drunk = 0u32;
+        for flower_entity in meadow.flowers_within(reach_box) {
+            let mut flower_entry = world.entry_mut(flower_entity).expect("flower disappeared");
+            let Position(flower_pos) = *flower_entry
+                .get_component::<Position>()
+                .expect("Flower missing pos");
+            let flower = flower_entry
+                .get_component_mut::<Flower>()
+                .expect("Flower missing flower data");
+            if flower.collected {
+                continue;
+            }
+            let flower_shape = Ball::new(flower.radius);
+            let flower_isometry = Isometry::translation(flower_pos.x, flower_pos.y);
+            if distance(&identity, &bee_shape, &flower_isometry, &flower_shape).unwrap() <= reach {
+                // Rarer flowers are worth more. Ones worth more than the bee
+                // has room for are left for later, unless it needs the drink.
+                let nectar = flora.get(flower.species).nectar;
+                if pollen + nectar > room && !thirsty {
+                    continue;
+                }
+                flower.collect(clock);
+                let taken = nectar.min(room - pollen);
+                pickings.add(flower.species, taken, flower_pos, clock);
+                pollen += taken;
+                nectar_drunk += nectar;
+                if let Some(power_up) = flower.power_up {
+                    effects.apply(power_up, clock, settings);
+                }
+                if pollen >= room && !thirsty {
+                    break;
+                }
+            }
+        }
+        if nectar_drunk > 0 {
+            let mut entry = world.entry_mut(entity).expect("Bee missing");
+            let bee = entry
+                .get_component_mut::<Bee>()
+                .expect("Bee missing bee data");
+            bee.pollen += pollen;
+            bee.energy = (bee.energy + nectar_drunk as f32 * settings.nectar_energy / 100.).min(1.);
+            *entry
+                .get_component_mut::<Effects>()
+                .expect("Bee missing effects") = effects;
         }
     }
-    if score_delta > 0 {
-        let mut bee = world.entry_mut(the_bee.entity).expect("Bee missing");
-        let bee = bee
-            .get_component_mut::<Bee>()
-            .expect("Bee missing bee data");
-        bee.score += score_delta;
+}
+
+/// The waypoints ahead of the bee
+#[system(for_each)]
+fn draw_route(waypoints: &Waypoints) {
+    for waypoint in &waypoints.0 {
+        draw_circle_lines(waypoint.x, waypoint.y, ARRIVED, 2., WHITE);
     }
 }
 
 #[system(for_each)]
+#[allow(clippy::too_many_arguments)]
 fn draw(
     bee: &Bee,
     pos: &Position,
+    prev: &PreviousPosition,
+    player: Option<&Player>,
+    effects: &Effects,
     #[resource] clock: &GameClock,
     #[resource] settings: &Settings,
     #[resource] texture: &Texture2D,
 ) {
-    let frame_num = ((settings.animation_speed as f64 * clock.time) as usize)
-        .rem(spritesheet::BEE_FLYING_FRAMES.len());
-    let animation_frame = &spritesheet::BEE_FLYING_FRAMES[frame_num];
-    let Position(pos) = *pos;
-    let points = bee.transform_rect(pos, settings, &BEE_SPRITE);
-    points.draw_sprite(*texture, animation_frame.uv, WHITE);
+    let size = effects.bee_size(clock, settings);
+    let (frames, sprite) = if bee.is_landed() {
+        (&spritesheet::BEE_WALKING_FRAMES[..], &BEE_WALKING_SPRITE)
+    } else {
+        (&spritesheet::BEE_FLYING_FRAMES[..], &BEE_SPRITE)
+    };
+    let frame_num = ((settings.animation_speed as f64 * clock.time) as usize).rem(frames.len());
+    let animation_frame = &frames[frame_num];
+    let pos = clock.interpolate(prev, pos);
+    let points = bee.transform_rect(pos, size, sprite);
+    let tint = if player.is_some() {
+        WHITE
+    } else {
+        WILD_BEE_TINT
+    };
+    points.draw_sprite(*texture, animation_frame.uv, tint);
     #[cfg(feature = "wireframes")]
     {
         points.draw_sides(0.5, YELLOW);
-        let hitbox = bee.transform_rect(pos, settings, &BEE_HITBOX);
+        let hitbox = bee.hitbox(pos, size);
         hitbox.draw_sides(0.5, RED);
         draw_circle_lines(pos.x, pos.y, 1., 0.5, YELLOW);
         draw_circle_lines(bee.destination.x, bee.destination.y, 2., 0.5, MAGENTA);
     }
 }
 
+/// How much energy the players' bees have left, just above them
+#[system(for_each)]
+fn draw_energy(
+    bee: &Bee,
+    pos: &Position,
+    prev: &PreviousPosition,
+    _player: &Player,
+    #[resource] clock: &GameClock,
+    #[resource] settings: &Settings,
+) {
+    let scale = settings.bee_size / 1000.;
+    let width = BEE_SPRITE.w * scale * 0.6;
+    let height = width / 8.;
+    let pos = clock.interpolate(prev, pos);
+    let x = pos.x - width / 2.;
+    let y = pos.y - BEE_SPRITE.h * scale * 0.6 - height;
+    let color = if bee.energy < TAKE_OFF_ENERGY {
+        RED
+    } else {
+        YELLOW
+    };
+    draw_rectangle(x, y, width, height, Color::new(0., 0., 0., 0.5));
+    draw_rectangle(x, y, width * bee.energy, height, color);
+}
+
+/// Each player's score, in their own view
 #[system(for_each)]
 fn draw_score(
     bee: &Bee,
+    player: &Player,
     #[resource] settings: &Settings,
     #[resource] camera: &crate::camera::Camera,
 ) {
+    if player.0 != camera.player {
+        return;
+    }
     let Rect { x, y, w, h } = camera.rect;
     let font = Font::default();
     let font_size = (settings.font_size / 10.) as u16;
     let font_scale = vec2(w, h).length() / settings.max_zoom;
-    let text = &format!("{}", bee.score);
+    let carrying = format!("{} +{}/{}", bee.score, bee.pollen, settings.pollen_capacity);
+    let text = &if settings.num_players() > 1 {
+        format!("P{} {}", player.0 + 1, carrying)
+    } else {
+        carrying
+    };
     let TextDimensions {
         width, offset_y, ..
     } = measure_text(text, Some(font), font_size, font_scale);
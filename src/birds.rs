@@ -0,0 +1,110 @@
+//! The birds.
+//!
+//! Keep an eye on the sky. Stay close to the flowers.
+
+use std::ops::Rem;
+
+use crate::{
+    enemies::{Enemy, Prowl},
+    meadow::Meadow,
+    prelude::*,
+    spritesheet,
+};
+use serde::{Deserialize, Serialize};
+
+/// The bird sprite rect translated so the bird position is at the origin
+const BIRD_SPRITE: Rect = Rect {
+    x: -(spritesheet::BIRD_FRAME_SIZE.x as f32 * 0.5),
+    y: -(spritesheet::BIRD_FRAME_SIZE.y as f32 * 0.5),
+    w: spritesheet::BIRD_FRAME_SIZE.x as f32,
+    h: spritesheet::BIRD_FRAME_SIZE.y as f32,
+};
+
+/// How much faster the wings go when a bird is diving
+const DIVE_FLAP: f64 = 2.;
+
+/// A bird. What it does is up to [`Enemy`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Bird;
+
+pub fn roll_call(
+    world: &mut legion::world::World,
+    _systems: &mut legion::systems::Builder,
+    resources: &mut legion::systems::Resources,
+) {
+    let meadow = resources.get::<Meadow>().expect("No meadow");
+    let settings = resources.get::<Settings>().expect("No settings");
+    let mut rng = resources.get_mut::<Rng>().expect("No rng");
+    let home = meadow.size / 2.;
+    for _ in 0..settings.num_birds {
+        let patrol = rng.gen_range(0.2, 0.45) * meadow.size.min_element();
+        let enemy = Enemy {
+            prowl: Prowl::Circling,
+            home,
+            patrol,
+            speed: settings.bird_speed,
+            agility: settings.bird_agility,
+            sight: settings.bird_sight,
+            reach: settings.bird_size / 8.,
+            bite: settings.bird_bite,
+            rest: settings.bird_rest_time as f64,
+        };
+        spawn(
+            world,
+            Bird,
+            enemy,
+            Position::from(meadow.rand_pos(&mut rng)),
+            Velocity::default(),
+        );
+    }
+}
+
+pub fn draw_call(
+    systems: &mut legion::systems::Builder,
+    _resources: &mut legion::systems::Resources,
+) {
+    systems.add_system(draw_system());
+}
+
+pub fn spawn(
+    world: &mut legion::world::World,
+    bird: Bird,
+    enemy: Enemy,
+    pos: Position,
+    vel: Velocity,
+) -> legion::Entity {
+    world.push((bird, enemy, pos, PreviousPosition::from(pos.0), vel))
+}
+
+#[system(for_each)]
+#[filter(legion::component::<Bird>())]
+fn draw(
+    enemy: &Enemy,
+    pos: &Position,
+    prev: &PreviousPosition,
+    vel: &Velocity,
+    #[resource] clock: &GameClock,
+    #[resource] settings: &Settings,
+    #[resource] texture: &Texture2D,
+) {
+    let frames = &spritesheet::BIRD_FRAMES;
+    let flap = match enemy.prowl {
+        Prowl::Diving => DIVE_FLAP,
+        _ => 1.,
+    };
+    let frame_num =
+        ((settings.animation_speed as f64 * flap * clock.time / 4.) as usize).rem(frames.len());
+    let pos = clock.interpolate(prev, pos);
+    let heading = vel.0.try_normalize().unwrap_or(Vec2::X);
+    let points = Quad::from_rect(&BIRD_SPRITE)
+        .scale_to_origin(settings.bird_size / BIRD_SPRITE.h)
+        .rotate_to(heading)
+        .translate(pos);
+    points.draw_sprite(*texture, frames[frame_num].uv, WHITE);
+    #[cfg(feature = "wireframes")]
+    {
+        points.draw_sides(0.5, YELLOW);
+        draw_circle_lines(pos.x, pos.y, enemy.reach, 0.5, RED);
+        draw_circle_lines(pos.x, pos.y, enemy.sight, 0.5, MAGENTA);
+    }
+}
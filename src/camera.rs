@@ -3,44 +3,59 @@
 //! Follow the bee, show us what is coming. Help us notice stuff. But be gentle,
 //! remember we are moving the bee with our touch.
 
-use legion::{world::SubWorld, EntityStore};
+use legion::{world::SubWorld, IntoQuery as _};
 
 use crate::prelude::*;
 
+/// How far the player can pinch in or out, as a multiple of
+/// [`Settings::max_zoom`]
+const PINCH_LIMITS: (f32, f32) = (0.25, 4.);
+
+/// One player's view of the meadow. Loaded as a resource while drawing for
+/// that player.
 #[derive(Clone, Copy)]
 pub struct Camera {
     pub rect: Rect,
     camera2d: Camera2D,
+    /// How far the player has pinched in. Above 1 is closer to the bee.
+    pinch: f32,
+    /// Whose bee to follow
+    pub player: usize,
+    /// How many players share the screen
+    players: usize,
 }
 
-pub fn roll_call(
-    _world: &mut legion::world::World,
+pub fn draw_call(
     systems: &mut legion::systems::Builder,
-    resources: &mut legion::systems::Resources,
+    _resources: &mut legion::systems::Resources,
 ) {
-    let camera = Camera {
-        rect: Rect::default(),
-        camera2d: Camera2D::default(),
-    };
-    resources.insert(camera);
     systems.add_system(follow_bee_system());
 }
 
+/// Follow the camera's player's bee, in the camera's part of the screen
 #[system]
+#[read_component(Player)]
 #[read_component(Position)]
+#[read_component(PreviousPosition)]
 #[read_component(Velocity)]
 fn follow_bee(
     world: &mut SubWorld,
     #[resource] camera: &mut Camera,
-    #[resource] the_bee: &TheBee,
+    #[resource] clock: &GameClock,
     #[resource] settings: &Settings,
 ) {
-    let bee = world.entry_ref(the_bee.entity).expect("Bee missing");
-    let Position(pos) = *bee.get_component::<Position>().expect("Bee missing pos");
-    let Velocity(vel) = *bee.get_component::<Velocity>().expect("Bee missing vel");
-    let screen = vec2(screen_width(), screen_height());
-    let aspect =
-        screen.normalize() * (vel.length() * settings.velocity_zoom / 10.).max(settings.max_zoom);
+    let bee = <(&Player, &Position, &PreviousPosition, &Velocity)>::query()
+        .iter(world)
+        .find(|(player, ..)| player.0 == camera.player);
+    // Settings can change the players mid round, so there may be no bee yet
+    let (_, pos, prev, &Velocity(vel)) = match bee {
+        Some(bee) => bee,
+        None => return,
+    };
+    let pos = clock.interpolate(prev, pos);
+    let viewport = camera.viewport();
+    let screen = vec2(viewport.w, viewport.h);
+    let aspect = screen.normalize() * view_size(vel, settings) / camera.pinch;
     let target = pos + vel - aspect / 2.;
     camera.rect = Rect {
         x: target.x,
@@ -48,12 +63,97 @@ fn follow_bee(
         w: aspect.x,
         h: aspect.y,
     };
-    camera.camera2d = Camera2D::from_display_rect(camera.rect);
+    camera.camera2d = Camera2D {
+        viewport: (camera.players > 1).then_some((
+            viewport.x as i32,
+            viewport.y as i32,
+            viewport.w as i32,
+            viewport.h as i32,
+        )),
+        ..Camera2D::from_display_rect(camera.rect)
+    };
     set_camera(&camera.camera2d);
 }
 
+/// How far a camera following a bee flying at `vel` can see, across the
+/// longer side of the screen, before the player pinches in or out
+fn view_size(vel: Vec2, settings: &Settings) -> f32 {
+    (vel.length() * settings.velocity_zoom / 10.).max(settings.max_zoom)
+}
+
+/// As much of the meadow as a camera following a bee at `pos`, flying at
+/// `vel`, would show on any shape of screen, pinched out as far as it goes
+pub fn sight(pos: Vec2, vel: Vec2, settings: &Settings) -> Rect {
+    let size = view_size(vel, settings) / PINCH_LIMITS.0;
+    let corner = pos + vel - vec2(size, size) / 2.;
+    Rect::new(corner.x, corner.y, size, size)
+}
+
+/// Go back to drawing on the whole screen, in pixels.
+pub fn full_screen() {
+    // The default camera leaves the viewport where it was
+    set_camera(&Camera2D::from_display_rect(Rect::new(
+        0.,
+        0.,
+        screen_width(),
+        screen_height(),
+    )));
+    set_default_camera();
+}
+
 impl Camera {
+    /// The camera for `player`, one of `players` sharing the screen
+    pub fn new(player: usize, players: usize) -> Self {
+        Camera {
+            rect: Rect::default(),
+            camera2d: Camera2D::default(),
+            pinch: 1.,
+            player,
+            players,
+        }
+    }
+
+    /// Where on the screen this camera draws, in pixels. The players get a
+    /// column each.
+    fn viewport(&self) -> Rect {
+        let width = screen_width() / self.players as f32;
+        Rect::new(width * self.player as f32, 0., width, screen_height())
+    }
+
     pub fn screen_to_world(&self, point: Vec2) -> Vec2 {
+        // The camera thinks its viewport is the whole screen
+        let viewport = self.viewport();
+        let point = (point - vec2(viewport.x, viewport.y)) / vec2(viewport.w, viewport.h)
+            * vec2(screen_width(), screen_height());
         self.camera2d.screen_to_world(point)
     }
+
+    /// Zoom in by `factor`, or out if it is below 1.
+    pub fn pinch(&mut self, factor: f32) {
+        self.pinch = (self.pinch * factor).clamp(PINCH_LIMITS.0, PINCH_LIMITS.1);
+    }
+
+    /// Draw in screen coordinates for a bit, then go back to following the
+    /// bee.
+    pub fn on_screen(&self, draw: impl FnOnce()) {
+        full_screen();
+        draw();
+        set_camera(&self.camera2d);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pinching_stops_at_the_limits() {
+        let mut camera = Camera::new(0, 1);
+        camera.pinch(2.);
+        assert_eq!(camera.pinch, 2.);
+        camera.pinch(100.);
+        assert_eq!(camera.pinch, PINCH_LIMITS.1);
+        camera.pinch(0.);
+        assert_eq!(camera.pinch, PINCH_LIMITS.0);
+    }
 }
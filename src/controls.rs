@@ -0,0 +1,253 @@
+//! The controls.
+//!
+//! However the player likes to fly: point with the mouse, drag a finger
+//! around, or push the bee around with the keys or a gamepad.
+
+use crate::{camera::Camera, prelude::*, settings::ControlScheme};
+
+/// How far a finger can wander, in pixels, and still be holding still
+const TOUCH_SLOP: f32 = 10.;
+
+/// How long a finger has to hold still, in seconds, to queue a waypoint
+const HOLD_TIME: f64 = 0.5;
+
+/// What the fingers on the screen are up to. Loaded as a resource.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Gesture {
+    /// No fingers
+    Idle,
+    /// One finger, not sure yet whether it is a tap, a drag or a hold
+    Touch { id: u64, start: Vec2, since: f64 },
+    /// One finger, steering the bee wherever it goes
+    Drag { id: u64 },
+    /// A finger that has done its thing, and is ignored until it lifts
+    Spent { id: u64 },
+    /// Two fingers, `spread` pixels apart
+    Pinch { spread: f32 },
+}
+
+/// The keys that push the bee left, right, up and down
+type Keys = [KeyCode; 4];
+const WASD: Keys = [KeyCode::A, KeyCode::D, KeyCode::W, KeyCode::S];
+const ARROWS: Keys = [KeyCode::Left, KeyCode::Right, KeyCode::Up, KeyCode::Down];
+
+/// Where the left stick of each gamepad is pushed, each axis in `-1..=1`.
+/// Loaded as a resource, and only ever has any with the `gamepad` feature,
+/// off the web.
+#[derive(Clone, Debug, PartialEq, Default)]
+struct GamepadSticks(Vec<Vec2>);
+
+pub fn cue_call(
+    systems: &mut legion::systems::Builder,
+    resources: &mut legion::systems::Resources,
+) {
+    resources.insert(Gesture::Idle);
+    resources.insert(GamepadSticks::default());
+    // Gilrs can't leave the main thread, so it can't be a resource.
+    #[cfg(all(feature = "gamepad", not(target_family = "wasm")))]
+    match gilrs::Gilrs::new() {
+        Ok(mut gilrs) => {
+            systems.add_thread_local_fn(move |_, resources| {
+                resources.insert(read_gamepads(&mut gilrs));
+            });
+        }
+        Err(err) => warn!("No gamepads: {}", err),
+    }
+    systems.add_system(inputs_system());
+}
+
+/// Read everyone's controls. The mouse and fingers always fly the first
+/// player's bee, and `camera` is the first player's.
+#[system]
+fn inputs(
+    #[resource] inputs: &mut Inputs,
+    #[resource] camera: &mut Camera,
+    #[resource] gesture: &mut Gesture,
+    #[resource] gamepads: &GamepadSticks,
+    #[resource] settings: &Settings,
+) {
+    let players = settings.num_players();
+    let mut gamepads = gamepads.0.iter().copied();
+    let touches = touches();
+    for (player, inputs) in inputs.players.iter_mut().enumerate().take(players) {
+        let scheme = settings.control_scheme(player);
+        // Hand the gamepads out in order, to whoever uses one
+        let gamepad = if scheme == ControlScheme::Gamepad {
+            gamepads.next().unwrap_or_default()
+        } else {
+            Vec2::ZERO
+        };
+        // Fingers work whatever the controls are, since phones have nothing
+        // else
+        if player == 0 && (!touches.is_empty() || *gesture != Gesture::Idle) {
+            let (steering, waypoint) = read_touches(&touches, gesture, camera);
+            inputs.steering = steering;
+            // Leave any waypoint for the simulation to pick up
+            if waypoint.is_some() {
+                inputs.waypoint = waypoint;
+            }
+            continue;
+        }
+        inputs.steering = match scheme {
+            ControlScheme::Mouse if player == 0 && is_mouse_button_down(MouseButton::Left) => Some(
+                Steering::Destination(camera.screen_to_world(mouse_position().into())),
+            ),
+            ControlScheme::Mouse => None,
+            // Sharing the keyboard, so one gets WASD and the other the arrows
+            ControlScheme::Keyboard => thrust(match (players, player) {
+                (1, _) => read_keyboard(&[WASD, ARROWS]),
+                (_, 0) => read_keyboard(&[WASD]),
+                _ => read_keyboard(&[ARROWS]),
+            }),
+            ControlScheme::Gamepad => thrust(gamepad),
+        };
+    }
+}
+
+/// Follow what the fingers are doing: one finger drags the destination
+/// around, or queues a waypoint if it holds still, and two fingers pinch the
+/// camera in or out.
+fn read_touches(
+    touches: &[Touch],
+    gesture: &mut Gesture,
+    camera: &mut Camera,
+) -> (Option<Steering>, Option<Vec2>) {
+    let down: Vec<&Touch> = touches
+        .iter()
+        .filter(|touch| !matches!(touch.phase, TouchPhase::Ended | TouchPhase::Cancelled))
+        .collect();
+    let mut steering = None;
+    let mut waypoint = None;
+    let current = *gesture;
+    *gesture = match (down.as_slice(), current) {
+        ([first, second, ..], _) => {
+            let spread = first.position.distance(second.position);
+            if let Gesture::Pinch { spread: before } = current {
+                if spread > 0. && before > 0. {
+                    camera.pinch(spread / before);
+                }
+            }
+            Gesture::Pinch { spread }
+        }
+        ([touch], Gesture::Touch { id, start, since }) if touch.id == id => {
+            let pos = camera.screen_to_world(touch.position);
+            if touch.position.distance(start) > TOUCH_SLOP {
+                steering = Some(Steering::Destination(pos));
+                Gesture::Drag { id }
+            } else if get_time() - since > HOLD_TIME {
+                waypoint = Some(pos);
+                Gesture::Spent { id }
+            } else {
+                current
+            }
+        }
+        ([touch], Gesture::Drag { id }) if touch.id == id => {
+            steering = Some(Steering::Destination(
+                camera.screen_to_world(touch.position),
+            ));
+            current
+        }
+        ([touch], Gesture::Spent { id }) if touch.id == id => current,
+        // What is left of a pinch is not a new touch
+        ([touch], Gesture::Pinch { .. }) => Gesture::Spent { id: touch.id },
+        ([touch], _) => Gesture::Touch {
+            id: touch.id,
+            start: touch.position,
+            since: get_time(),
+        },
+        ([], Gesture::Touch { id, .. }) => {
+            // A quick tap, so head straight there
+            if let Some(touch) = touches.iter().find(|touch| touch.id == id) {
+                steering = Some(Steering::Destination(
+                    camera.screen_to_world(touch.position),
+                ));
+            }
+            Gesture::Idle
+        }
+        ([], _) => Gesture::Idle,
+    };
+    (steering, waypoint)
+}
+
+/// Only steer while something is being pushed
+fn thrust(push: Vec2) -> Option<Steering> {
+    if push == Vec2::ZERO {
+        None
+    } else {
+        Some(Steering::Thrust(push))
+    }
+}
+
+/// Which way any of `keys` push
+fn read_keyboard(keys: &[Keys]) -> Vec2 {
+    let axis = |less: usize, more: usize| {
+        let down = |n: usize| keys.iter().any(|keys| is_key_down(keys[n]));
+        match (down(less), down(more)) {
+            (true, false) => -1.,
+            (false, true) => 1.,
+            _ => 0.,
+        }
+    };
+    vec2(axis(0, 1), axis(2, 3))
+}
+
+/// The left stick of every gamepad
+#[cfg(all(feature = "gamepad", not(target_family = "wasm")))]
+fn read_gamepads(gilrs: &mut gilrs::Gilrs) -> GamepadSticks {
+    // Catch up on events so the gamepad state is current
+    while gilrs.next_event().is_some() {}
+    let sticks = gilrs
+        .gamepads()
+        .map(|(_, gamepad)| {
+            // Up on the stick is down in the meadow
+            vec2(
+                gamepad.value(gilrs::Axis::LeftStickX),
+                -gamepad.value(gilrs::Axis::LeftStickY),
+            )
+        })
+        .collect();
+    GamepadSticks(sticks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_steer_while_pushing() {
+        assert_eq!(thrust(Vec2::ZERO), None);
+        assert_eq!(thrust(vec2(-1., 0.)), Some(Steering::Thrust(vec2(-1., 0.))));
+    }
+
+    fn finger(id: u64, x: f32) -> Touch {
+        Touch {
+            id,
+            phase: TouchPhase::Moved,
+            position: vec2(x, 0.),
+        }
+    }
+
+    #[test]
+    fn two_fingers_pinch_and_then_are_spent() {
+        let mut camera = Camera::new(0, 1);
+        let mut gesture = Gesture::Idle;
+        let pinch = [finger(1, 100.), finger(2, 200.)];
+        assert_eq!(
+            read_touches(&pinch, &mut gesture, &mut camera),
+            (None, None)
+        );
+        assert_eq!(gesture, Gesture::Pinch { spread: 100. });
+        let wider = [finger(1, 50.), finger(2, 250.)];
+        read_touches(&wider, &mut gesture, &mut camera);
+        assert_eq!(gesture, Gesture::Pinch { spread: 200. });
+        // The finger left behind does not steer
+        let lifted = [finger(2, 250.)];
+        assert_eq!(
+            read_touches(&lifted, &mut gesture, &mut camera),
+            (None, None)
+        );
+        assert_eq!(gesture, Gesture::Spent { id: 2 });
+        assert_eq!(read_touches(&[], &mut gesture, &mut camera), (None, None));
+        assert_eq!(gesture, Gesture::Idle);
+    }
+}
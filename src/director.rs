@@ -0,0 +1,139 @@
+//! The director.
+//!
+//! Calls the scenes: the title, the round itself, the breaks, and the bows
+//! at the end.
+
+use crate::{
+    bee::Bee,
+    camera::Camera,
+    meadow::{Flower, Meadow},
+    prelude::*,
+};
+use legion::{world::SubWorld, IntoQuery as _};
+
+pub fn cue_call(
+    systems: &mut legion::systems::Builder,
+    _resources: &mut legion::systems::Resources,
+) {
+    systems.add_system(direct_system());
+}
+
+pub fn roll_call(
+    _world: &mut legion::world::World,
+    systems: &mut legion::systems::Builder,
+    _resources: &mut legion::systems::Resources,
+) {
+    systems.add_system(round_over_system());
+}
+
+pub fn draw_call(
+    systems: &mut legion::systems::Builder,
+    _resources: &mut legion::systems::Resources,
+) {
+    systems.add_system(draw_overlay_system());
+}
+
+/// Move between the scenes when the player asks to.
+#[system]
+fn direct(#[resource] state: &mut GameState) {
+    let go_on = is_mouse_button_pressed(MouseButton::Left)
+        || is_key_pressed(KeyCode::Space)
+        || is_key_pressed(KeyCode::Enter);
+    *state = match *state {
+        GameState::Title if go_on => GameState::Playing,
+        GameState::Title if is_key_pressed(KeyCode::Escape) => GameState::Quit,
+        GameState::Playing if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::P) => {
+            GameState::Paused
+        }
+        GameState::Paused if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::P) => {
+            GameState::Playing
+        }
+        GameState::Paused if is_key_pressed(KeyCode::N) => GameState::NewRound,
+        GameState::Paused if is_key_pressed(KeyCode::Q) => GameState::Quit,
+        GameState::Results if go_on => GameState::NewRound,
+        GameState::Results if is_key_pressed(KeyCode::Escape) => GameState::Quit,
+        state => state,
+    };
+}
+
+/// The round is over when time runs out, or there is nothing left to collect
+/// or bring home.
+#[system]
+#[read_component(Flower)]
+#[read_component(Bee)]
+fn round_over(
+    world: &mut SubWorld,
+    #[resource] state: &mut GameState,
+    #[resource] meadow: &Meadow,
+    #[resource] clock: &GameClock,
+    #[resource] settings: &Settings,
+) {
+    let out_of_time = !settings.endless && clock.time >= settings.round_time as f64;
+    // Unless the flowers grow back, or there are always more, the round is
+    // over once they are all picked and taken home
+    let picked_clean = settings.regrowth_time <= 0.
+        && !meadow.is_infinite()
+        && <&Flower>::query()
+            .iter(world)
+            .all(|flower| flower.collected)
+        && <&Bee>::query().iter(world).all(|bee| bee.pollen() == 0);
+    if out_of_time || picked_clean {
+        *state = GameState::Results;
+    }
+}
+
+#[system]
+#[read_component(Bee)]
+#[read_component(Player)]
+fn draw_overlay(world: &mut SubWorld, #[resource] state: &GameState, #[resource] camera: &Camera) {
+    let lines = match *state {
+        GameState::Title => vec!["Bumble Umble Gee".to_owned(), "Click to start".to_owned()],
+        GameState::Paused => vec![
+            "Paused".to_owned(),
+            "Esc to keep going, N for a new meadow, Q to quit".to_owned(),
+        ],
+        GameState::Results => {
+            let mut scores: Vec<_> = <(&Player, &Bee)>::query()
+                .iter(world)
+                .map(|(player, bee)| (player.0, bee.score()))
+                .collect();
+            scores.sort_unstable();
+            let mut lines = vec!["Time's up!".to_owned()];
+            match scores.as_slice() {
+                [(_, score)] => lines.push(format!("You brought home {} pollen", score)),
+                scores => lines.extend(scores.iter().map(|(player, score)| {
+                    format!("Player {} brought home {} pollen", player + 1, score)
+                })),
+            }
+            let best_rival = <&Bee>::query()
+                .filter(!legion::component::<Player>())
+                .iter(world)
+                .map(Bee::score)
+                .max();
+            if let Some(best_rival) = best_rival {
+                lines.push(format!("The best rival brought home {}", best_rival));
+            }
+            lines.push("Click for a new meadow".to_owned());
+            lines
+        }
+        GameState::Playing | GameState::NewRound | GameState::Quit => return,
+    };
+    camera.on_screen(|| {
+        draw_rectangle(
+            0.,
+            0.,
+            screen_width(),
+            screen_height(),
+            Color::new(0., 0., 0., 0.4),
+        );
+        // The first line is the headline, the rest are smaller
+        let mut y = screen_height() / 3.;
+        for (n, line) in lines.iter().enumerate() {
+            let font_size = screen_height() / if n == 0 { 8. } else { 20. };
+            let TextDimensions { width, height, .. } =
+                measure_text(line, None, font_size as u16, 1.);
+            draw_text(line, (screen_width() - width) / 2., y, font_size, WHITE);
+            y += height * 2.;
+        }
+    });
+}
@@ -0,0 +1,199 @@
+//! The enemies.
+//!
+//! Not everyone in the meadow is here for the flowers. Some of them are here
+//! for the bees.
+
+use crate::{bee::Bee, hive::Hive, meadow::Meadow, power_ups::Effects, prelude::*};
+use legion::{world::SubWorld, Entity, EntityStore as _, IntoQuery as _};
+use parry2d::{math::Isometry, query::distance, shape::Ball};
+use serde::{Deserialize, Serialize};
+
+/// How much of its top speed an enemy uses when it is not chasing anything
+const CRUISE: f32 = 0.6;
+/// How far ahead on its circle an enemy aims, in radians
+const LOOK_AHEAD: f32 = 0.3;
+/// The furthest ahead an enemy will guess where a bee is going, in seconds
+const MAX_PREDICTION: f32 = 2.;
+
+/// What an enemy is up to
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Prowl {
+    /// Going round and round its patch, looking for a bee in the open
+    Circling,
+    /// Going after the nearest bee in the open
+    Diving,
+    /// Full, until the [`GameClock`] gets to `until`
+    Retreating { until: f64 },
+}
+
+/// Something that hunts bees
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Enemy {
+    pub prowl: Prowl,
+    /// The middle of the patch it circles
+    pub home: Vec2,
+    /// How far out from `home` it circles
+    pub patrol: f32,
+    /// How fast it can go
+    pub speed: f32,
+    /// How fast it can change its velocity
+    pub agility: f32,
+    /// How far away it can spot a bee
+    pub sight: f32,
+    /// How close it has to get to catch a bee
+    pub reach: f32,
+    /// How much pollen it knocks out of a bee it catches
+    pub bite: u32,
+    /// How long it leaves the bees alone after catching one, in seconds
+    pub rest: f64,
+}
+
+pub fn roll_call(
+    _world: &mut legion::world::World,
+    systems: &mut legion::systems::Builder,
+    _resources: &mut legion::systems::Resources,
+) {
+    systems.add_system(prowl_system());
+}
+
+/// The velocity change that turns `vel` toward `target` at `speed`
+fn seek(pos: Vec2, vel: Vec2, target: Vec2, speed: f32) -> Vec2 {
+    (target - pos).normalize_or_zero() * speed - vel
+}
+
+/// Like [`seek`], but aimed at where a target moving at `target_vel` will be
+/// by the time we get there
+fn pursue(pos: Vec2, vel: Vec2, target: Vec2, target_vel: Vec2, speed: f32) -> Vec2 {
+    let prediction = (pos.distance(target) / speed).min(MAX_PREDICTION);
+    seek(pos, vel, target + target_vel * prediction, speed)
+}
+
+/// Like [`seek`], but aimed a little ahead on a circle around `center`
+fn orbit(pos: Vec2, vel: Vec2, center: Vec2, radius: f32, speed: f32) -> Vec2 {
+    let offset = pos - center;
+    let angle = offset.y.atan2(offset.x) + LOOK_AHEAD;
+    seek(
+        pos,
+        vel,
+        center + vec2(angle.cos(), angle.sin()) * radius,
+        speed,
+    )
+}
+
+/// Enemies circle until they spot a bee out in the open, then chase it down.
+/// Catching one costs it pollen, and buys the bees a little peace.
+#[system]
+#[write_component(Enemy)]
+#[write_component(Velocity)]
+#[write_component(Bee)]
+#[read_component(Position)]
+#[read_component(Effects)]
+#[read_component(Hive)]
+fn prowl(
+    world: &mut SubWorld,
+    #[resource] meadow: &Meadow,
+    #[resource] clock: &GameClock,
+    #[resource] settings: &Settings,
+) {
+    let (hive, hive_pos) = {
+        let entry = world.entry_ref(meadow.hive).expect("Hive missing");
+        let hive = *entry.get_component::<Hive>().expect("Hive missing hive");
+        let Position(pos) = *entry.get_component::<Position>().expect("Hive missing pos");
+        (hive, pos)
+    };
+    let exposed: Vec<(Entity, Vec2, Vec2, Quad)> =
+        <(Entity, &Bee, &Position, &Velocity, &Effects)>::query()
+            .iter(world)
+            .filter(|(_, _, pos, ..)| {
+                pos.0.distance(hive_pos) > hive.radius
+                    && meadow
+                        .flowers_within(Rect::new(pos.0.x, pos.0.y, 0., 0.))
+                        .next()
+                        .is_none()
+            })
+            .map(|(entity, bee, pos, vel, effects)| {
+                let hitbox = bee.hitbox(pos.0, effects.bee_size(clock, settings));
+                (*entity, pos.0, vel.0, hitbox)
+            })
+            .collect();
+    let dt = clock.tick.as_secs_f32();
+    let mut caught = Vec::new();
+    for (enemy, pos, vel) in <(&mut Enemy, &Position, &mut Velocity)>::query().iter_mut(world) {
+        let Position(pos) = *pos;
+        if let Prowl::Retreating { until } = enemy.prowl {
+            if clock.time >= until {
+                enemy.prowl = Prowl::Circling;
+            }
+        }
+        let prey = match enemy.prowl {
+            Prowl::Retreating { .. } => None,
+            _ => exposed
+                .iter()
+                .filter(|(_, bee_pos, ..)| bee_pos.distance(pos) <= enemy.sight)
+                .min_by(|a, b| a.1.distance(pos).total_cmp(&b.1.distance(pos))),
+        };
+        let steering = match prey {
+            Some((entity, bee_pos, bee_vel, hitbox)) => {
+                enemy.prowl = Prowl::Diving;
+                let reach = Ball::new(enemy.reach);
+                let isometry = Isometry::translation(pos.x, pos.y);
+                if distance(&Isometry::identity(), &hitbox.polyline(), &isometry, &reach).unwrap()
+                    == 0.
+                {
+                    caught.push((*entity, enemy.bite));
+                    enemy.prowl = Prowl::Retreating {
+                        until: clock.time + enemy.rest,
+                    };
+                }
+                pursue(pos, vel.0, *bee_pos, *bee_vel, enemy.speed)
+            }
+            None => {
+                if enemy.prowl == Prowl::Diving {
+                    enemy.prowl = Prowl::Circling;
+                }
+                orbit(pos, vel.0, enemy.home, enemy.patrol, enemy.speed * CRUISE)
+            }
+        };
+        let Velocity(v) = *vel;
+        *vel = Velocity::from(
+            (v + steering.clamp_length_max(enemy.agility * dt)).clamp_length_max(enemy.speed),
+        );
+    }
+    for (entity, bite) in caught {
+        let mut bee = world.entry_mut(entity).expect("Bee missing");
+        bee.get_component_mut::<Bee>()
+            .expect("Bee missing bee data")
+            .drop_pollen(bite);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seek_turns_toward_the_target_at_speed() {
+        let change = seek(Vec2::ZERO, vec2(0., 10.), vec2(100., 0.), 20.);
+        assert_eq!(vec2(0., 10.) + change, vec2(20., 0.));
+    }
+
+    #[test]
+    fn pursue_leads_a_moving_target() {
+        let still = pursue(Vec2::ZERO, Vec2::ZERO, vec2(100., 0.), Vec2::ZERO, 50.);
+        let fleeing = pursue(Vec2::ZERO, Vec2::ZERO, vec2(100., 0.), vec2(0., 50.), 50.);
+        assert_eq!(still, vec2(50., 0.));
+        assert!(fleeing.y > 0., "aimed at {:?}", fleeing);
+        // But not too far ahead, however slow it is
+        let slow = pursue(Vec2::ZERO, Vec2::ZERO, vec2(1000., 0.), vec2(0., 50.), 1.);
+        assert!(slow.y / slow.x <= 50. * MAX_PREDICTION / 1000. + 1e-6);
+    }
+
+    #[test]
+    fn orbit_goes_round_the_same_way() {
+        // Always the way the angle grows, so from the right of the center
+        // that is toward +y
+        let change = orbit(vec2(100., 0.), Vec2::ZERO, Vec2::ZERO, 100., 10.);
+        assert!(change.y > 0. && change.x < 0., "{:?}", change);
+        assert!((change.length() - 10.).abs() < 1e-4);
+    }
+}
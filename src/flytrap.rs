@@ -0,0 +1,252 @@
+//! The flytraps.
+//!
+//! Not every flower is friendly. These ones look just like the rest, right
+//! up until they bite.
+
+use crate::{
+    bee::Bee,
+    meadow::{rand_flower_color, Meadow},
+    power_ups::Effects,
+    prelude::*,
+    spritesheet,
+};
+use legion::{world::SubWorld, Entity, EntityStore as _, IntoQuery as _};
+use parry2d::{math::Isometry, query::intersection_test, shape::Ball};
+use serde::{Deserialize, Serialize};
+
+/// The flytrap sprite rect translated so the flytrap position is at the
+/// origin.
+const FLYTRAP_SPRITE: Rect = Rect {
+    x: -(spritesheet::FLYTRAP_FRAME_SIZE.x as f32 * 0.5),
+    y: -(spritesheet::FLYTRAP_FRAME_SIZE.y as f32 * 0.5),
+    w: spritesheet::FLYTRAP_FRAME_SIZE.x as f32,
+    h: spritesheet::FLYTRAP_FRAME_SIZE.y as f32,
+};
+/// A flytrap disguised as a flower, the same way [`meadow`](crate::meadow)
+/// draws them.
+const FLOWER_SPRITE: Rect = Rect {
+    x: -(spritesheet::FLOWER_FRAME_SIZE.x as f32 * 0.5),
+    y: -(spritesheet::FLOWER_FRAME_SIZE.y as f32 * 0.5),
+    w: spritesheet::FLOWER_FRAME_SIZE.x as f32,
+    h: spritesheet::FLOWER_FRAME_SIZE.y as f32,
+};
+
+/// How much further than its petals a flytrap can reach
+const TRIGGER_SCALE: f32 = 1.5;
+/// How long a flytrap takes to set itself again after letting a bee go, in
+/// seconds
+const REARM_TIME: f64 = 5.;
+
+/// What a flytrap is up to
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Trap {
+    /// Pretending to be a flower
+    Lurking,
+    /// Holding a bee until the [`GameClock`] gets to `until`
+    Shut { until: f64 },
+    /// Showing its teeth until it is ready to lurk again at `until`
+    Open { until: f64 },
+}
+
+/// A flower that catches bees
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Flytrap {
+    #[serde(with = "crate::settings::ColorDef")]
+    color: Color,
+    pub radius: f32,
+    pub trap: Trap,
+}
+
+pub fn roll_call(
+    world: &mut legion::world::World,
+    systems: &mut legion::systems::Builder,
+    resources: &mut legion::systems::Resources,
+) {
+    {
+        let meadow = resources.get::<Meadow>().expect("No meadow");
+        let settings = resources.get::<Settings>().expect("No settings");
+        let mut rng = resources.get_mut::<Rng>().expect("No rng");
+        for _ in 0..settings.num_flytraps {
+            let pos = meadow.rand_pos(&mut rng);
+            let color = rand_flower_color(&mut rng);
+            let radius = rng.gen_range(settings.flower_size.start, settings.flower_size.end);
+            world.push((
+                Flytrap {
+                    color,
+                    radius,
+                    trap: Trap::Lurking,
+                },
+                Position::from(pos),
+            ));
+        }
+    }
+    systems.add_system(snap_system());
+}
+
+pub fn draw_call(
+    systems: &mut legion::systems::Builder,
+    _resources: &mut legion::systems::Resources,
+) {
+    systems.add_system(draw_system());
+}
+
+/// Snap shut on any bee that comes too close, and let it go again later.
+#[system]
+#[write_component(Flytrap)]
+#[write_component(Bee)]
+#[read_component(Position)]
+#[read_component(Effects)]
+fn snap(world: &mut SubWorld, #[resource] clock: &GameClock, #[resource] settings: &Settings) {
+    let bees: Vec<(Entity, Bee, Vec2, Effects)> = <(Entity, &Bee, &Position, &Effects)>::query()
+        .iter(world)
+        .filter(|(_, bee, ..)| !bee.is_trapped(clock))
+        .map(|(entity, bee, pos, effects)| (*entity, *bee, pos.0, *effects))
+        .collect();
+    let identity = Isometry::identity();
+    let mut caught = Vec::new();
+    for (flytrap, pos) in <(&mut Flytrap, &Position)>::query().iter_mut(world) {
+        flytrap.trap = match flytrap.trap {
+            Trap::Shut { until } if clock.time >= until => Trap::Open {
+                until: clock.time + REARM_TIME,
+            },
+            Trap::Open { until } if clock.time >= until => Trap::Lurking,
+            Trap::Lurking => {
+                let trigger = Ball::new(flytrap.radius * TRIGGER_SCALE);
+                let isometry = Isometry::translation(pos.0.x, pos.0.y);
+                let victim = bees.iter().find(|(entity, bee, bee_pos, effects)| {
+                    !caught.iter().any(|(caught, _)| caught == entity)
+                        && intersection_test(
+                            &identity,
+                            &bee.hitbox(*bee_pos, effects.bee_size(clock, settings))
+                                .polyline(),
+                            &isometry,
+                            &trigger,
+                        )
+                        .unwrap()
+                });
+                match victim {
+                    Some((entity, ..)) => {
+                        let until = clock.time + settings.flytrap_hold_time as f64;
+                        caught.push((*entity, until));
+                        Trap::Shut { until }
+                    }
+                    None => Trap::Lurking,
+                }
+            }
+            trap => trap,
+        };
+    }
+    for (entity, until) in caught {
+        let mut bee = world.entry_mut(entity).expect("Bee missing");
+        let bee = bee
+            .get_component_mut::<Bee>()
+            .expect("Bee missing bee data");
+        bee.trap(until);
+        bee.drop_pollen(settings.flytrap_bite);
+    }
+}
+
+#[system(for_each)]
+fn draw(flytrap: &Flytrap, pos: &Position, #[resource] texture: &Texture2D) {
+    let Position(pos) = *pos;
+    let scale = |sprite: &Rect| {
+        Quad::from_rect(sprite)
+            .scale_to_origin(flytrap.radius / (sprite.w / 2.))
+            .translate(pos)
+    };
+    match flytrap.trap {
+        Trap::Lurking => {
+            let points = scale(&FLOWER_SPRITE);
+            points.draw_sprite(*texture, spritesheet::FLOWER_FRAMES[0].uv, flytrap.color);
+            points.draw_sprite(*texture, spritesheet::FLOWER_FRAMES[1].uv, WHITE);
+        }
+        Trap::Shut { .. } => {
+            scale(&FLYTRAP_SPRITE).draw_sprite(*texture, spritesheet::FLYTRAP_FRAMES[1].uv, WHITE)
+        }
+        Trap::Open { .. } => {
+            scale(&FLYTRAP_SPRITE).draw_sprite(*texture, spritesheet::FLYTRAP_FRAMES[0].uv, WHITE)
+        }
+    }
+    #[cfg(feature = "wireframes")]
+    draw_circle_lines(pos.x, pos.y, flytrap.radius * TRIGGER_SCALE, 0.5, MAGENTA);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bee::Waypoints;
+    use legion::{Resources, Schedule, World};
+
+    #[test]
+    fn snaps_shut_then_lets_go() {
+        let settings = Settings {
+            flytrap_hold_time: 3.,
+            ..Settings::default()
+        };
+        let mut world = World::default();
+        let pos = vec2(100., 100.);
+        let flytrap = world.push((
+            Flytrap {
+                color: WHITE,
+                radius: 20.,
+                trap: Trap::Lurking,
+            },
+            Position::from(pos),
+        ));
+        let bee = crate::bee::spawn(
+            &mut world,
+            Bee::new(pos, 1.),
+            Position::from(pos),
+            Velocity::default(),
+            Waypoints::default(),
+        );
+        let mut resources = Resources::default();
+        resources.insert(settings);
+        resources.insert(GameClock::default());
+        let mut schedule = Schedule::builder().add_system(snap_system()).build();
+        let mut at = |world: &mut World, time: f64| {
+            resources
+                .get_mut::<GameClock>()
+                .expect("missing clock")
+                .time = time;
+            schedule.execute(world, &mut resources);
+            let trap = world
+                .entry_ref(flytrap)
+                .expect("flytrap missing")
+                .get_component::<Flytrap>()
+                .expect("flytrap missing flytrap data")
+                .trap;
+            let bee = *world
+                .entry_ref(bee)
+                .expect("bee missing")
+                .get_component::<Bee>()
+                .expect("bee missing bee data");
+            let clock = GameClock {
+                time,
+                ..GameClock::default()
+            };
+            (trap, bee.is_trapped(&clock), bee.pollen())
+        };
+        // Nothing to bite off, but caught all the same
+        assert_eq!(at(&mut world, 1.), (Trap::Shut { until: 4. }, true, 0));
+        assert_eq!(at(&mut world, 3.), (Trap::Shut { until: 4. }, true, 0));
+        assert_eq!(
+            at(&mut world, 4.),
+            (
+                Trap::Open {
+                    until: 4. + REARM_TIME
+                },
+                false,
+                0
+            )
+        );
+        // The bee is still there, but the trap has to set itself again first
+        assert_eq!(
+            at(&mut world, 5.).0,
+            Trap::Open {
+                until: 4. + REARM_TIME
+            }
+        );
+        assert_eq!(at(&mut world, 4. + REARM_TIME).0, Trap::Lurking);
+    }
+}
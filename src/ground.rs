@@ -0,0 +1,242 @@
+//! The ground.
+//!
+//! Grass, dirt, water and stone, pieced together a tile at a time so that
+//! every tile gets along with its neighbours.
+
+use std::num::NonZeroU32;
+
+use direction::CardinalDirectionTable;
+use serde::{Deserialize, Serialize};
+use wfc::{retry, wrap::WrapNone, GlobalStats, PatternDescription, PatternTable, RunOwn, Size};
+
+use crate::prelude::*;
+
+const DIRT: Color = Color::new(0.52, 0.37, 0.22, 1.);
+const POND: Color = Color::new(0.2, 0.42, 0.75, 1.);
+const POND_SHINE: Color = Color::new(0.35, 0.58, 0.88, 1.);
+const ROCK: Color = Color::new(0.5, 0.5, 0.48, 1.);
+const ROCK_SHADOW: Color = Color::new(0.32, 0.32, 0.3, 1.);
+
+/// What a patch of ground is
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tile {
+    Grass,
+    Dirt,
+    Pond,
+    Rock,
+}
+impl Tile {
+    /// Every tile, in the order `wfc` knows them by
+    const ALL: [Tile; 4] = [Tile::Grass, Tile::Dirt, Tile::Pond, Tile::Rock];
+
+    /// How common the tile is, next to the others
+    fn weight(self) -> u32 {
+        match self {
+            Tile::Grass => 12,
+            Tile::Dirt => 4,
+            Tile::Pond => 3,
+            Tile::Rock => 1,
+        }
+    }
+
+    /// What can sit beside the tile, in any direction. Ponds keep to the
+    /// grass, and the rocks to the paths.
+    fn neighbours(self) -> &'static [Tile] {
+        match self {
+            Tile::Grass => &Tile::ALL,
+            Tile::Dirt => &[Tile::Grass, Tile::Dirt, Tile::Rock],
+            Tile::Pond => &[Tile::Grass, Tile::Pond],
+            Tile::Rock => &[Tile::Grass, Tile::Dirt, Tile::Rock],
+        }
+    }
+
+    /// How likely a flower is to take root here, in `0..=1`
+    pub fn fertility(self) -> f32 {
+        match self {
+            Tile::Grass => 1.,
+            Tile::Dirt => 0.2,
+            Tile::Pond | Tile::Rock => 0.,
+        }
+    }
+}
+
+/// The tiles under a chunk of the meadow
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Ground {
+    tile_size: f32,
+    columns: usize,
+    rows: usize,
+    /// Row by row
+    tiles: Vec<Tile>,
+}
+impl Ground {
+    /// Lay out tiles over a chunk of `size`, with a `verge` of grass all
+    /// round if it has to get along with chunks laid out on their own
+    pub fn new(size: Vec2, verge: bool, settings: &Settings, rng: &mut Rng) -> Self {
+        let tile_size = settings.tile_size;
+        let columns = (size.x / tile_size).ceil().max(1.) as usize;
+        let rows = (size.y / tile_size).ceil().max(1.) as usize;
+        let index = |tile: &Tile| Tile::ALL.iter().position(|t| t == tile).unwrap() as u32;
+        let stats = GlobalStats::new(
+            Tile::ALL
+                .iter()
+                .map(|tile| {
+                    PatternDescription::new(
+                        NonZeroU32::new(tile.weight()),
+                        // Not `new_fn`, which drops the uninitialised `Vec`s
+                        // it writes over
+                        CardinalDirectionTable::new_array(array_init::array_init(|_| {
+                            tile.neighbours().iter().map(index).collect()
+                        })),
+                    )
+                })
+                .collect::<PatternTable<_>>(),
+        );
+        // Grass goes next to anything, so there is always a way out
+        let wave = RunOwn::new_wrap(
+            Size::new(columns as u32, rows as u32),
+            &stats,
+            WrapNone,
+            rng,
+        )
+        .collapse_retrying(retry::Forever, rng);
+        let mut tiles: Vec<Tile> = wave
+            .grid()
+            .iter()
+            .map(|cell| Tile::ALL[cell.chosen_pattern_id().expect("Tile not collapsed") as usize])
+            .collect();
+        // Grass goes next to anything, on either side of the verge
+        if verge {
+            for row in 0..rows {
+                for column in 0..columns {
+                    if row == 0 || column == 0 || row == rows - 1 || column == columns - 1 {
+                        tiles[row * columns + column] = Tile::Grass;
+                    }
+                }
+            }
+        }
+        Ground {
+            tile_size,
+            columns,
+            rows,
+            tiles,
+        }
+    }
+
+    /// The tile under `pos`
+    pub fn tile_at(&self, pos: Vec2) -> Tile {
+        let column = ((pos.x / self.tile_size) as usize).min(self.columns - 1);
+        let row = ((pos.y / self.tile_size) as usize).min(self.rows - 1);
+        self.tiles[row * self.columns + column]
+    }
+
+    /// Draw the tiles that are at least partly inside `rect`, with the first
+    /// tile at `origin`
+    pub fn draw(&self, origin: Vec2, rect: Rect, settings: &Settings) {
+        let first = |from: f32| (from / self.tile_size).floor().max(0.) as usize;
+        let last =
+            |to: f32, count: usize| ((to / self.tile_size).ceil().max(0.) as usize).min(count);
+        let size = self.tile_size;
+        let rect = rect.offset(-origin);
+        for row in first(rect.top())..last(rect.bottom(), self.rows) {
+            for column in first(rect.left())..last(rect.right(), self.columns) {
+                let (x, y) = (
+                    origin.x + column as f32 * size,
+                    origin.y + row as f32 * size,
+                );
+                match self.tiles[row * self.columns + column] {
+                    Tile::Grass => draw_rectangle(x, y, size, size, settings.meadow_color),
+                    Tile::Dirt => draw_rectangle(x, y, size, size, DIRT),
+                    Tile::Pond => {
+                        draw_rectangle(x, y, size, size, POND);
+                        draw_circle(x + size * 0.3, y + size * 0.3, size * 0.08, POND_SHINE);
+                    }
+                    Tile::Rock => {
+                        draw_rectangle(x, y, size, size, settings.meadow_color);
+                        let (cx, cy) = (x + size * 0.5, y + size * 0.5);
+                        draw_circle(cx + size * 0.05, cy + size * 0.05, size * 0.35, ROCK_SHADOW);
+                        draw_circle(cx, cy, size * 0.35, ROCK);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ground(verge: bool) -> Ground {
+        let settings = Settings::default();
+        let mut rng = Rng::new(21);
+        Ground::new(
+            vec2(30., 20.) * settings.tile_size,
+            verge,
+            &settings,
+            &mut rng,
+        )
+    }
+
+    #[test]
+    fn neighbours_get_along() {
+        let ground = ground(false);
+        assert_eq!((ground.columns, ground.rows), (30, 20));
+        let tile = |column: usize, row: usize| ground.tiles[row * ground.columns + column];
+        for row in 0..ground.rows {
+            for column in 0..ground.columns {
+                let here = tile(column, row);
+                if column + 1 < ground.columns {
+                    let right = tile(column + 1, row);
+                    assert!(
+                        here.neighbours().contains(&right),
+                        "{:?} by {:?}",
+                        here,
+                        right
+                    );
+                }
+                if row + 1 < ground.rows {
+                    let below = tile(column, row + 1);
+                    assert!(
+                        here.neighbours().contains(&below),
+                        "{:?} by {:?}",
+                        here,
+                        below
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn neighbours_are_mutual() {
+        for &tile in &Tile::ALL {
+            for neighbour in tile.neighbours() {
+                assert!(
+                    neighbour.neighbours().contains(&tile),
+                    "{:?} {:?}",
+                    tile,
+                    neighbour
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn a_verge_is_all_grass() {
+        let ground = ground(true);
+        let edge = ground.tile_size * 0.5;
+        let far =
+            vec2(ground.columns as f32, ground.rows as f32) * ground.tile_size - Vec2::splat(edge);
+        for x in 0..ground.columns {
+            let x = (x as f32 + 0.5) * ground.tile_size;
+            assert_eq!(ground.tile_at(vec2(x, edge)), Tile::Grass);
+            assert_eq!(ground.tile_at(vec2(x, far.y)), Tile::Grass);
+        }
+        for y in 0..ground.rows {
+            let y = (y as f32 + 0.5) * ground.tile_size;
+            assert_eq!(ground.tile_at(vec2(edge, y)), Tile::Grass);
+            assert_eq!(ground.tile_at(vec2(far.x, y)), Tile::Grass);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! The hive.
+//!
+//! Home sweet home. Pollen only counts once it makes it back here.
+
+use crate::{bee::Bee, prelude::*};
+use legion::{world::SubWorld, IntoQuery as _};
+use serde::{Deserialize, Serialize};
+
+/// Where the bees bring their pollen. Planted in the middle of the meadow.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Hive {
+    pub radius: f32,
+}
+
+pub fn roll_call(
+    _world: &mut legion::world::World,
+    systems: &mut legion::systems::Builder,
+    _resources: &mut legion::systems::Resources,
+) {
+    systems.add_system(deliver_system());
+}
+
+pub fn draw_call(
+    systems: &mut legion::systems::Builder,
+    _resources: &mut legion::systems::Resources,
+) {
+    systems.add_system(draw_system());
+}
+
+/// Any bee at a hive banks whatever pollen it is carrying.
+#[system]
+#[read_component(Hive)]
+#[read_component(Position)]
+#[write_component(Bee)]
+fn deliver(world: &mut SubWorld) {
+    let hives: Vec<(Hive, Vec2)> = <(&Hive, &Position)>::query()
+        .iter(world)
+        .map(|(hive, pos)| (*hive, pos.0))
+        .collect();
+    for (bee, pos) in <(&mut Bee, &Position)>::query().iter_mut(world) {
+        if hives
+            .iter()
+            .any(|(hive, hive_pos)| hive_pos.distance(pos.0) < hive.radius)
+        {
+            bee.bank_pollen();
+        }
+    }
+}
+
+#[system(for_each)]
+fn draw(hive: &Hive, pos: &Position) {
+    let Position(pos) = *pos;
+    draw_poly(pos.x, pos.y, 6, hive.radius, 30., GOLD);
+    draw_poly_lines(pos.x, pos.y, 6, hive.radius, 30., hive.radius / 10., BROWN);
+}
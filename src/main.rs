@@ -1,48 +1,181 @@
-use crate::settings::Settings;
+use crate::{prelude::GameState, recording::Recording, save::SaveGame, settings::Settings};
 #[cfg(feature = "console")]
 use egui_macroquad::egui;
 use macroquad::prelude::*;
+use std::path::PathBuf;
 
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 mod backstage;
 mod bee;
+mod birds;
 mod camera;
+mod controls;
+mod director;
+mod enemies;
+mod flytrap;
+mod ground;
+mod hive;
 mod meadow;
+mod planting;
+mod power_ups;
 mod prelude;
+mod recording;
+mod rivals;
+mod save;
 mod settings;
+mod species;
 mod spritesheet;
+mod storage;
+mod web;
+mod wind;
+
+/// How often to save the game, in seconds
+const AUTOSAVE_INTERVAL: f64 = 10.;
+
+/// Command line arguments
+#[derive(Debug, Default)]
+struct Args {
+    /// `--record <path>`: Write a recording of each round to `path`
+    record: Option<PathBuf>,
+    /// `--replay <path>`: Play back the recording in `path`
+    replay: Option<PathBuf>,
+}
+impl Args {
+    fn from_env() -> Self {
+        let mut args = Args::default();
+        let mut env_args = std::env::args().skip(1);
+        while let Some(arg) = env_args.next() {
+            let path = env_args.next().map(PathBuf::from);
+            match arg.as_str() {
+                "--record" => args.record = path,
+                "--replay" => args.replay = path,
+                _ => panic!("Unknown argument {}", arg),
+            }
+        }
+        args
+    }
+}
 
 #[macroquad::main("BumbleUmbleGee")]
 async fn main() {
-    let mut stage_manager = backstage::StageManager::new(Settings::default());
-    while !stage_manager.settings().want_quit() {
-        stage_manager = {
-            let settings = stage_manager.settings().clone();
-            backstage::StageManager::new(settings)
+    let args = Args::from_env();
+    let replay = args
+        .replay
+        .as_ref()
+        .map(|path| Recording::load(path).expect("Could not load the replay"));
+    let mut settings = match &replay {
+        Some(replay) => replay.settings.clone(),
+        None => Settings::load(),
+    };
+    // Pick up where we left off, unless we are recording or replaying since
+    // those only work from the start of a round.
+    let mut saved = if args.record.is_none() && replay.is_none() {
+        SaveGame::fetch()
+    } else {
+        None
+    };
+    loop {
+        let mut stage_manager = match saved.take() {
+            Some(save) => backstage::StageManager::resume(&save),
+            None => backstage::StageManager::new(settings.seeded()),
         };
-        while !stage_manager.settings().want_restart() {
+        let seed = stage_manager.settings().seed;
+        if let Some(replay) = &replay {
+            stage_manager.play_back(replay);
+        } else if args.record.is_some() {
+            stage_manager.start_recording();
+        }
+        let mut saved_at = get_time();
+        let mut previous_state = stage_manager.state();
+        loop {
+            let state = stage_manager.state();
+            // Keep the round in progress safe, unless we are only watching it
+            if replay.is_none() {
+                if in_round(state) && get_time() - saved_at > AUTOSAVE_INTERVAL {
+                    save_game(&stage_manager);
+                    saved_at = get_time();
+                }
+                if state == GameState::Quit && in_round(previous_state) {
+                    save_game(&stage_manager);
+                } else if in_round(previous_state) && !in_round(state) {
+                    discard_saved_game();
+                }
+            }
+            if matches!(state, GameState::NewRound | GameState::Quit) {
+                break;
+            }
+            previous_state = state;
             // Process keys, mouse etc.
             #[cfg(feature = "console")]
-            egui_macroquad::ui(|egui_ctx| {
-                let mut settings = stage_manager.settings();
-                egui::Window::new("Settings").show(egui_ctx, |ui| {
-                    settings.egui(ui);
+            {
+                let mut next_state = None;
+                egui_macroquad::ui(|egui_ctx| {
+                    let mut settings = stage_manager.settings();
+                    egui::Window::new("Settings").show(egui_ctx, |ui| {
+                        ui.horizontal(|ui| {
+                            if ui.button("Restart").clicked() {
+                                next_state = Some(GameState::NewRound);
+                            }
+                            if ui.button("Quit").clicked() {
+                                next_state = Some(GameState::Quit);
+                            }
+                            if ui.button("Save settings").clicked() {
+                                if let Err(err) = settings.store() {
+                                    warn!("Could not save the settings: {}", err);
+                                }
+                            }
+                        });
+                        settings.egui(ui);
+                    });
                 });
-            });
+                if let Some(state) = next_state {
+                    stage_manager.set_state(state);
+                }
+            }
             stage_manager.execute();
             #[cfg(feature = "console")]
             egui_macroquad::draw();
             next_frame().await;
-            if is_key_pressed(KeyCode::Escape) {
-                stage_manager.settings().quit = true;
+            if stage_manager.replay_finished() {
+                info!("Replay finished with a score of {}", stage_manager.score());
+                stage_manager.set_state(GameState::Quit);
             }
         }
+        // Keep any changes from the console, but a seed that was only picked
+        // for this round is not kept for the next one
+        let round_settings = stage_manager.settings().clone();
+        settings = if round_settings.seed == seed {
+            Settings {
+                seed: settings.seed,
+                ..round_settings
+            }
+        } else {
+            round_settings
+        };
+        if let (Some(path), Some(recording)) = (&args.record, stage_manager.recording()) {
+            recording.save(path).expect("Could not save the recording");
+        }
+        if stage_manager.state() == GameState::Quit {
+            break;
+        }
+    }
+}
+
+/// Is there a round going that would be worth saving?
+fn in_round(state: GameState) -> bool {
+    matches!(state, GameState::Playing | GameState::Paused)
+}
+
+fn save_game(stage_manager: &backstage::StageManager) {
+    if let Err(err) = stage_manager.save_game().store() {
+        warn!("Could not save the game: {}", err);
+    }
+}
+
+fn discard_saved_game() {
+    if let Err(err) = SaveGame::discard() {
+        warn!("Could not throw out the saved game: {}", err);
     }
-    #[cfg(all(feature = "console", not(target_family = "wasm")))]
-    println!(
-        "Copy this to the default() function in src/settings.rs\n{:#?}",
-        *stage_manager.settings()
-    );
 }
@@ -2,14 +2,19 @@
 //!
 //! Give us somewhere to frolic!
 
-use legion::{world::SubWorld, Entity, EntityStore as _};
-use macroquad::rand::gen_range;
-use static_aabb2d_index::{StaticAABB2DIndex, StaticAABB2DIndexBuilder};
+use std::collections::{BTreeMap, BTreeSet};
 
-use crate::{prelude::*, spritesheet};
+use legion::{systems::CommandBuffer, world::SubWorld, Entity, EntityStore as _, IntoQuery as _};
+use serde::{Deserialize, Serialize};
+use static_aabb2d_index::{Control, StaticAABB2DIndex, StaticAABB2DIndexBuilder};
 
-/// This is the bees sprite rect translated so the bee position is at the
-/// origin.
+use crate::{
+    bee::Bee, ground::Ground, hive::Hive, planting, power_ups::PowerUp, prelude::*, species::Flora,
+    spritesheet, web::Web, wind::Wind,
+};
+
+/// The flower sprite rect translated so the flower position is at the
+/// origin. The pollen is drawn from it, whatever the petals look like.
 const FLOWER_SPRITE: Rect = Rect {
     x: -(spritesheet::FLOWER_FRAME_SIZE.x as f32 * 0.5),
     y: -(spritesheet::FLOWER_FRAME_SIZE.y as f32 * 0.5),
@@ -17,31 +22,405 @@ const FLOWER_SPRITE: Rect = Rect {
     h: spritesheet::FLOWER_FRAME_SIZE.y as f32,
 };
 
+/// How long the name of a picked flower hangs in the air, in seconds
+const PICKING_TIME: f64 = 1.5;
+/// How far the name floats up while it does
+const PICKING_RISE: f32 = 40.;
+
+/// How far past what the bees can see to plant chunks, in chunks
+const PLANT_MARGIN: f32 = 0.5;
+/// How far past what the bees can see to keep chunks planted, in chunks. More
+/// than [`PLANT_MARGIN`], so a bee on the edge of a chunk does not plant and
+/// dig it up over and over.
+const KEEP_MARGIN: f32 = 1.5;
+
+/// How far a flower leans downwind, in flower radii per unit of wind speed
+const SWAY: f32 = 0.005;
+/// How much a flower twists back and forth in the wind, in radians per unit
+/// of wind speed
+const FLUTTER: f32 = 0.004;
+
+/// Which chunk of the meadow, by column and row
+pub type ChunkKey = (i32, i32);
+
+/// The flowers that were picked in a chunk, by the order they grew in, and
+/// when by the [`GameClock`]
+pub type Picked = Vec<(usize, f64)>;
+
 /// The meadow
 #[derive(Debug)]
 pub struct Meadow {
+    /// The part of the meadow around the hive, where everyone starts out.
+    /// All of it, unless the meadow goes on forever.
     pub size: Vec2,
-    pub flower_index: StaticAABB2DIndex<f32>,
-    pub flower_entities: Vec<Entity>,
+    /// How big each chunk is, when the meadow goes on forever
+    chunk_size: Option<f32>,
+    pub hive: Entity,
+    /// The chunks that are planted. Just the one, unless the meadow goes on
+    /// forever.
+    chunks: BTreeMap<ChunkKey, Chunk>,
+    /// The picked flowers in chunks that were dug up, so they have not grown
+    /// their nectar back any quicker when the bees return
+    picked: BTreeMap<ChunkKey, Picked>,
 }
 impl Meadow {
-    pub fn new(world: &mut legion::world::World, settings: &Settings) -> Self {
+    pub fn new(
+        world: &mut legion::world::World,
+        settings: &Settings,
+        flora: &Flora,
+        rng: &mut Rng,
+    ) -> Self {
         let meadow_size = vec2(settings.meadow_height * 100., settings.meadow_width * 100.);
-        let num_flowers = settings.num_flowers * 10;
-        let mut flower_index_builder = StaticAABB2DIndexBuilder::new(num_flowers);
-        let mut flower_entities = Vec::with_capacity(num_flowers);
-        for _ in 0..num_flowers {
-            let pos = rand_pos(&meadow_size);
-            let color = rand_flower_color();
-            let radius = gen_range(settings.flower_size.start, settings.flower_size.end);
-            flower_entities.push(world.push((
-                Flower {
-                    color,
-                    radius,
-                    collected: false,
-                },
-                Position::from(pos),
-            )));
+        let mut meadow = Self::plant(
+            world,
+            settings,
+            meadow_size,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+        );
+        // A meadow that goes on forever is planted as the bees get to it
+        if !meadow.is_infinite() {
+            meadow.grow(world, (0, 0), settings, flora, rng);
+        }
+        meadow
+    }
+
+    /// Put `flowers` and `webs` in the `world`, each in its own chunk, in a
+    /// meadow of `size` laid over the `grounds` of its chunks, with the hive
+    /// in the middle. The chunks that are not planted remember what was
+    /// `picked` in them.
+    pub fn plant(
+        world: &mut legion::world::World,
+        settings: &Settings,
+        size: Vec2,
+        grounds: Vec<(ChunkKey, Ground)>,
+        flowers: Vec<(ChunkKey, Flower, Position)>,
+        webs: Vec<(ChunkKey, Web, Position)>,
+        picked: Vec<(ChunkKey, Picked)>,
+    ) -> Self {
+        let hive = world.push((
+            Hive {
+                radius: settings.hive_size,
+            },
+            Position::from(size / 2.),
+        ));
+        let mut meadow = Meadow {
+            size,
+            chunk_size: settings.infinite_meadow.then(|| settings.chunk_size()),
+            hive,
+            chunks: BTreeMap::new(),
+            picked: picked.into_iter().collect(),
+        };
+        let mut chunk_flowers: BTreeMap<ChunkKey, Vec<_>> = BTreeMap::new();
+        for (key, flower, pos) in flowers {
+            chunk_flowers.entry(key).or_default().push((flower, pos));
+        }
+        let mut chunk_webs: BTreeMap<ChunkKey, Vec<_>> = BTreeMap::new();
+        for (key, web, pos) in webs {
+            chunk_webs.entry(key).or_default().push((web, pos));
+        }
+        for (key, ground) in grounds {
+            let rect = meadow.chunk_rect(key);
+            let flowers = chunk_flowers.remove(&key).unwrap_or_default();
+            let webs = chunk_webs.remove(&key).unwrap_or_default();
+            let chunk = Chunk::plant(world, rect, ground, flowers, webs);
+            meadow.chunks.insert(key, chunk);
+        }
+        debug_assert!(
+            chunk_flowers.is_empty() && chunk_webs.is_empty(),
+            "Flowers or webs in a chunk with no ground"
+        );
+        meadow
+    }
+
+    /// Plant the chunk at `key`, with its share of the flowers and webs
+    fn grow(
+        &mut self,
+        world: &mut legion::world::World,
+        key: ChunkKey,
+        settings: &Settings,
+        flora: &Flora,
+        rng: &mut Rng,
+    ) {
+        let rect = self.chunk_rect(key);
+        let origin = rect.point();
+        let size = rect.size();
+        // Each chunk grows on its own, so they need a verge to line up
+        let ground = Ground::new(size, self.is_infinite(), settings, rng);
+        // As crowded as the meadow around the hive, with the odd one left
+        // over going to whoever the dice favour
+        let share = (size.x * size.y) / (self.size.x * self.size.y);
+        let mut count = |total: usize| {
+            let count = total as f32 * share;
+            count as usize + (rng.gen_range(0., 1.) < count.fract()) as usize
+        };
+        let num_flowers = count(settings.num_flowers * 10);
+        let num_webs = count(settings.num_webs);
+        let spots = planting::sow(
+            settings.flower_layout,
+            num_flowers,
+            size,
+            &ground,
+            settings.flower_size.end * flora.largest(),
+            rng,
+        );
+        let mut flowers: Vec<_> = spots
+            .into_iter()
+            .map(|pos| {
+                let species = flora.rand(rng);
+                let color = flora
+                    .get(species)
+                    .rand_color(rng)
+                    .unwrap_or_else(|| rand_flower_color(rng));
+                let scale = &flora.get(species).size;
+                let radius = rng.gen_range(settings.flower_size.start, settings.flower_size.end)
+                    * rng.gen_range(scale.start, scale.end);
+                let power_up = PowerUp::rand(settings, rng);
+                (
+                    Flower {
+                        species,
+                        color,
+                        radius,
+                        collected: false,
+                        collected_at: 0.,
+                        power_up,
+                    },
+                    Position::from(origin + pos),
+                )
+            })
+            .collect();
+        // The same flowers grow back every time, but not their nectar
+        for (n, collected_at) in self.picked.remove(&key).unwrap_or_default() {
+            if let Some((flower, _)) = flowers.get_mut(n) {
+                flower.collected = true;
+                flower.collected_at = collected_at;
+            }
+        }
+        let webs = (0..num_webs)
+            .map(|_| {
+                let pos = origin + rand_pos(&size, rng);
+                (Web::new(settings.web_size), Position::from(pos))
+            })
+            .collect();
+        let chunk = Chunk::plant(world, rect, ground, flowers, webs);
+        self.chunks.insert(key, chunk);
+    }
+
+    /// Plant the chunks at `wanted` that are not already planted, and dig
+    /// up any that are not in `kept`
+    fn stream(
+        &mut self,
+        world: &mut legion::world::World,
+        wanted: &BTreeSet<ChunkKey>,
+        kept: &BTreeSet<ChunkKey>,
+        settings: &Settings,
+        flora: &Flora,
+    ) {
+        let gone: Vec<ChunkKey> = self
+            .chunks
+            .keys()
+            .filter(|key| !kept.contains(key))
+            .copied()
+            .collect();
+        for key in gone {
+            let chunk = self.chunks.remove(&key).expect("Chunk missing");
+            let picked: Picked = chunk
+                .flower_entities
+                .iter()
+                .enumerate()
+                .filter_map(|(n, &entity)| {
+                    let flower = *world
+                        .entry_ref(entity)
+                        .expect("flower disappeared")
+                        .get_component::<Flower>()
+                        .expect("Flower missing flower data");
+                    flower.collected.then_some((n, flower.collected_at))
+                })
+                .collect();
+            if !picked.is_empty() {
+                self.picked.insert(key, picked);
+            }
+            for entity in chunk.flower_entities.into_iter().chain(chunk.web_entities) {
+                world.remove(entity);
+            }
+        }
+        for &key in wanted {
+            if !self.chunks.contains_key(&key) {
+                // Each chunk has dice of its own, so it grows the same
+                // whenever, and in whatever order, the bees get to it
+                let mut rng = Rng::new(chunk_seed(settings.seed, key));
+                self.grow(world, key, settings, flora, &mut rng);
+            }
+        }
+    }
+
+    /// Whether the meadow goes on forever
+    pub fn is_infinite(&self) -> bool {
+        self.chunk_size.is_some()
+    }
+
+    /// The chunk that `point` is in, planted or not
+    fn chunk_at(&self, point: Vec2) -> ChunkKey {
+        match self.chunk_size {
+            Some(chunk_size) => (
+                (point.x / chunk_size).floor() as i32,
+                (point.y / chunk_size).floor() as i32,
+            ),
+            None => (0, 0),
+        }
+    }
+
+    /// Where the chunk at `key` is
+    fn chunk_rect(&self, (column, row): ChunkKey) -> Rect {
+        match self.chunk_size {
+            Some(chunk_size) => Rect::new(
+                column as f32 * chunk_size,
+                row as f32 * chunk_size,
+                chunk_size,
+                chunk_size,
+            ),
+            None => Rect::new(0., 0., self.size.x, self.size.y),
+        }
+    }
+
+    /// Every chunk, planted or not, that is at least partly inside `rect`
+    fn chunks_around(&self, rect: Rect) -> impl Iterator<Item = ChunkKey> {
+        let (left, top) = self.chunk_at(rect.point());
+        let (right, bottom) = self.chunk_at(rect.point() + rect.size());
+        (top..=bottom).flat_map(move |row| (left..=right).map(move |column| (column, row)))
+    }
+
+    /// The planted chunks with anything at least partly inside `rect`,
+    /// counting flowers that hang over the edge of their chunk
+    fn chunks_within(&self, rect: Rect) -> impl Iterator<Item = &Chunk> + '_ {
+        self.chunks
+            .values()
+            .filter(move |chunk| chunk.bounds.overlaps(&rect))
+    }
+
+    /// Every planted flower, and the chunk it is in, in the order they were
+    /// planted
+    pub fn flower_entities(&self) -> impl Iterator<Item = (ChunkKey, Entity)> + '_ {
+        self.chunks.iter().flat_map(|(&key, chunk)| {
+            chunk
+                .flower_entities
+                .iter()
+                .map(move |&entity| (key, entity))
+        })
+    }
+
+    /// Every web, and the chunk it is in
+    pub fn web_entities(&self) -> impl Iterator<Item = (ChunkKey, Entity)> + '_ {
+        self.chunks
+            .iter()
+            .flat_map(|(&key, chunk)| chunk.web_entities.iter().map(move |&entity| (key, entity)))
+    }
+
+    /// What was picked in the chunks that are not planted
+    pub fn picked(&self) -> Vec<(ChunkKey, Picked)> {
+        self.picked
+            .iter()
+            .map(|(&key, picked)| (key, picked.clone()))
+            .collect()
+    }
+
+    /// The ground under every planted chunk
+    pub fn grounds(&self) -> Vec<(ChunkKey, Ground)> {
+        self.chunks
+            .iter()
+            .map(|(&key, chunk)| (key, chunk.ground.clone()))
+            .collect()
+    }
+
+    /// Keep `point` in the meadow, if the meadow has an edge
+    pub fn clamp(&self, point: Vec2) -> Vec2 {
+        if self.is_infinite() {
+            point
+        } else {
+            point.clamp(Vec2::ZERO, self.size)
+        }
+    }
+
+    pub fn rand_pos(&self, rng: &mut Rng) -> Vec2 {
+        rand_pos(&self.size, rng)
+    }
+
+    /// The flowers that are at least partly inside `rect`
+    pub fn flowers_within(&self, rect: Rect) -> impl Iterator<Item = Entity> + '_ {
+        self.chunks_within(rect).flat_map(move |chunk| {
+            chunk.flower_index.iter().flat_map(move |index| {
+                index
+                    .query_iter(rect.left(), rect.top(), rect.right(), rect.bottom())
+                    .map(move |n| chunk.flower_entities[n])
+            })
+        })
+    }
+
+    /// The flower nearest to `point` that `wanted` likes the look of
+    pub fn nearest_flower(
+        &self,
+        point: Vec2,
+        mut wanted: impl FnMut(Entity) -> bool,
+    ) -> Option<Entity> {
+        let mut nearest: Option<(f32, Entity)> = None;
+        for chunk in self.chunks.values() {
+            if let Some(index) = &chunk.flower_index {
+                index.visit_neighbors(point.x, point.y, &mut |n, distance| {
+                    if nearest.is_some_and(|(best, _)| best <= distance) {
+                        return Control::Break(());
+                    }
+                    let entity = chunk.flower_entities[n];
+                    if !wanted(entity) {
+                        return Control::Continue;
+                    }
+                    nearest = Some((distance, entity));
+                    Control::Break(())
+                });
+            }
+        }
+        nearest.map(|(_, entity)| entity)
+    }
+}
+
+/// A piece of the meadow, and everything growing in it
+#[derive(Debug)]
+struct Chunk {
+    /// Where the chunk is
+    rect: Rect,
+    /// The chunk, and all of every flower in it
+    bounds: Rect,
+    ground: Ground,
+    /// Empty chunks have no index
+    flower_index: Option<StaticAABB2DIndex<f32>>,
+    flower_entities: Vec<Entity>,
+    web_entities: Vec<Entity>,
+}
+impl Chunk {
+    /// Put `flowers` and `webs` in the `world`, on top of the `ground` at
+    /// `rect`
+    fn plant(
+        world: &mut legion::world::World,
+        rect: Rect,
+        ground: Ground,
+        flowers: Vec<(Flower, Position)>,
+        webs: Vec<(Web, Position)>,
+    ) -> Self {
+        let web_entities = world.extend(webs).to_vec();
+        let mut flower_index_builder = StaticAABB2DIndexBuilder::new(flowers.len());
+        let mut flower_entities = Vec::with_capacity(flowers.len());
+        let mut bounds = rect;
+        for (flower, pos) in flowers {
+            let Position(pos) = pos;
+            let radius = flower.radius;
+            flower_entities.push(world.push((flower, Position::from(pos))));
+            bounds = bounds.combine_with(Rect::new(
+                pos.x - radius,
+                pos.y - radius,
+                radius * 2.,
+                radius * 2.,
+            ));
             flower_index_builder.add(
                 pos.x - radius,
                 pos.y - radius,
@@ -49,35 +428,57 @@ impl Meadow {
                 pos.y + radius,
             );
         }
-        Meadow {
-            size: meadow_size,
+        Chunk {
+            rect,
+            bounds,
+            ground,
+            flower_index: flower_index_builder.build().ok(),
             flower_entities,
-            flower_index: flower_index_builder.build().unwrap(),
+            web_entities,
         }
     }
+}
 
-    pub fn clamp(&self, point: Vec2) -> Vec2 {
-        point.clamp(Vec2::ZERO, self.size)
-    }
-
-    pub fn rand_pos(&self) -> Vec2 {
-        rand_pos(&self.size)
-    }
-
-    pub fn flower_index_within(&self, rect: Rect) -> impl Iterator<Item = usize> + '_ {
-        self.flower_index
-            .query_iter(rect.left(), rect.top(), rect.right(), rect.bottom())
-    }
+/// The seed for the chunk at `key`, in a meadow grown from `seed`
+fn chunk_seed(seed: u64, (column, row): ChunkKey) -> u64 {
+    // Spread the neighbours far apart, so they grow nothing alike
+    seed ^ (column as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (row as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
 }
-fn rand_pos(size: &Vec2) -> Vec2 {
-    vec2(gen_range(0., size.x), gen_range(0., size.y))
+/// Anywhere in a meadow of `size`
+pub fn rand_pos(size: &Vec2, rng: &mut Rng) -> Vec2 {
+    vec2(rng.gen_range(0., size.x), rng.gen_range(0., size.y))
 }
 /// A flower
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Flower {
+    /// Which of the [`Flora`] it is
+    pub species: usize,
+    #[serde(with = "crate::settings::ColorDef")]
     color: Color,
     pub radius: f32,
     pub collected: bool,
+    /// When the nectar was taken, by the [`GameClock`]
+    collected_at: f64,
+    pub power_up: Option<PowerUp>,
+}
+impl Flower {
+    pub fn collect(&mut self, clock: &GameClock) {
+        self.collected = true;
+        self.collected_at = clock.time;
+    }
+
+    /// How far the nectar has grown back, in `0..=1`. Flowers that never
+    /// regrow stay at 0.
+    pub fn regrowth(&self, clock: &GameClock, settings: &Settings) -> f32 {
+        if !self.collected {
+            1.
+        } else if settings.regrowth_time <= 0. {
+            0.
+        } else {
+            ((clock.time - self.collected_at) as f32 / settings.regrowth_time).min(1.)
+        }
+    }
 }
 
 pub fn roll_call(
@@ -87,55 +488,210 @@ pub fn roll_call(
 ) {
     let meadow = {
         let settings = resources.get::<Settings>().expect("Missing settings");
-        Meadow::new(world, &settings)
+        let flora = resources.get::<Flora>().expect("Missing flora");
+        let mut rng = resources.get_mut::<Rng>().expect("Missing rng");
+        Meadow::new(world, &settings, &flora, &mut rng)
     };
     resources.insert(meadow);
+    resources.insert(Pickings::default());
+    systems.add_system(update_position_system());
+    systems.add_system(stream_system());
+    systems.add_system(regrow_system());
+    systems.add_system(wilt_system());
+}
+
+pub fn draw_call(
+    systems: &mut legion::systems::Builder,
+    _resources: &mut legion::systems::Resources,
+) {
     systems
-        .add_system(update_position_system())
         .add_system(draw_ground_system())
         .flush()
         .add_system(draw_flower_system())
-        .flush();
+        .flush()
+        .add_system(draw_pickings_system());
 }
 
 #[system(for_each)]
-fn update_position(pos: &mut Position, vel: &Velocity, #[resource] clock: &GameClock) {
+fn update_position(
+    pos: &mut Position,
+    prev: &mut PreviousPosition,
+    vel: &Velocity,
+    #[resource] clock: &GameClock,
+) {
     let Position(p) = *pos;
     let Velocity(v) = *vel;
+    *prev = PreviousPosition::from(p);
     *pos = Position::from(p + v * clock.tick.as_secs_f32())
 }
 
+/// A flower that was just picked
+#[derive(Clone, Copy, Debug)]
+struct Picking {
+    species: usize,
+    nectar: u32,
+    pos: Vec2,
+    time: f64,
+}
+
+/// Flowers that were just picked, so everyone can see what they were.
+/// Loaded as a resource.
+#[derive(Clone, Debug, Default)]
+pub struct Pickings(Vec<Picking>);
+impl Pickings {
+    /// Show off a flower of `species` at `pos`, worth `nectar`
+    pub fn add(&mut self, species: usize, nectar: u32, pos: Vec2, clock: &GameClock) {
+        self.0.push(Picking {
+            species,
+            nectar,
+            pos,
+            time: clock.time,
+        });
+    }
+}
+
+/// Forget flowers that were picked a while ago
+#[system]
+fn wilt(#[resource] pickings: &mut Pickings, #[resource] clock: &GameClock) {
+    pickings
+        .0
+        .retain(|picking| clock.time - picking.time < PICKING_TIME);
+}
+
+/// In a meadow that goes on forever, plant the chunks the bees are about to
+/// see and dig up the ones they have left far behind. What a bee can see is
+/// worked out here rather than by the cameras, so the meadow grows the same
+/// with or without a window.
+#[system]
+#[read_component(Bee)]
+#[read_component(Position)]
+#[read_component(Velocity)]
+fn stream(
+    world: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] meadow: &Meadow,
+    #[resource] settings: &Settings,
+) {
+    let chunk_size = match meadow.chunk_size {
+        Some(chunk_size) => chunk_size,
+        None => return,
+    };
+    let sights: Vec<Rect> = <(&Bee, &Position, &Velocity)>::query()
+        .iter(world)
+        .map(|(_, pos, vel)| crate::camera::sight(pos.0, vel.0, settings))
+        .collect();
+    let around = |margin: f32| -> BTreeSet<ChunkKey> {
+        let margin = margin * chunk_size;
+        sights
+            .iter()
+            .flat_map(|sight| {
+                meadow.chunks_around(Rect::new(
+                    sight.x - margin,
+                    sight.y - margin,
+                    sight.w + margin * 2.,
+                    sight.h + margin * 2.,
+                ))
+            })
+            .collect()
+    };
+    let wanted = around(PLANT_MARGIN);
+    let kept = around(KEEP_MARGIN);
+    if wanted.iter().all(|key| meadow.chunks.contains_key(key))
+        && meadow.chunks.keys().all(|key| kept.contains(key))
+    {
+        return;
+    }
+    // Planting needs the whole world, so wait for the other systems
+    commands.exec_mut(move |world, resources| {
+        let settings = resources.get::<Settings>().expect("Missing settings");
+        let flora = resources.get::<Flora>().expect("Missing flora");
+        resources
+            .get_mut::<Meadow>()
+            .expect("Missing meadow")
+            .stream(world, &wanted, &kept, &settings, &flora);
+    });
+}
+
+/// Picked flowers fill back up with nectar, given time
+#[system(for_each)]
+fn regrow(flower: &mut Flower, #[resource] clock: &GameClock, #[resource] settings: &Settings) {
+    if flower.collected && flower.regrowth(clock, settings) >= 1. {
+        flower.collected = false;
+    }
+}
+
 #[system]
-fn draw_ground(#[resource] _: &Meadow, #[resource] settings: &Settings) {
-    clear_background(settings.meadow_color);
+fn draw_ground(
+    #[resource] camera: &crate::camera::Camera,
+    #[resource] meadow: &Meadow,
+    #[resource] settings: &Settings,
+) {
+    let Rect { x, y, w, h } = camera.rect;
+    draw_rectangle(x, y, w, h, settings.meadow_color);
+    for chunk in meadow.chunks_within(camera.rect) {
+        chunk.ground.draw(chunk.rect.point(), camera.rect, settings);
+    }
 }
 
 #[system]
 #[read_component(Position)]
 #[read_component(Flower)]
+#[allow(clippy::too_many_arguments)]
 fn draw_flower(
     world: &mut SubWorld,
     #[resource] camera: &mut crate::camera::Camera,
     #[resource] meadow: &Meadow,
+    #[resource] clock: &GameClock,
+    #[resource] wind: &Wind,
+    #[resource] settings: &Settings,
+    #[resource] flora: &Flora,
     #[resource] texture: &Texture2D,
 ) {
-    for flower_entry in meadow.flower_index_within(camera.rect).map(|index| {
-        world
-            .entry_ref(meadow.flower_entities[index])
-            .expect("flower disappeared")
-    }) {
+    for flower_entry in meadow
+        .flowers_within(camera.rect)
+        .map(|entity| world.entry_ref(entity).expect("flower disappeared"))
+    {
         let Position(pos) = *flower_entry
             .get_component::<Position>()
             .expect("Flower missing pos");
         let flower = flower_entry
             .get_component::<Flower>()
             .expect("Flower missing flower data");
-        let points = Quad::from_rect(&FLOWER_SPRITE)
-            .scale_to_origin(flower.radius / (FLOWER_SPRITE.w / 2.))
-            .translate(pos);
-        points.draw_sprite(*texture, spritesheet::FLOWER_FRAMES[0].uv, flower.color);
+        // Lean the petals downwind, twisting a bit as they go
+        let gust = wind.at(pos, clock, settings);
+        let twist = FLUTTER * gust.length() * (clock.time as f32 * 5. + pos.x + pos.y).sin();
+        let place = |sprite: &Rect, scale: f32| {
+            Quad::from_rect(sprite)
+                .scale_to_origin(scale * flower.radius / (sprite.w / 2.))
+                .rotate_to(vec2(twist.cos(), twist.sin()))
+                .translate(pos + gust * SWAY * flower.radius)
+        };
+        let (petals_sprite, petals) = flora.get(flower.species).sprite.petals();
+        place(&petals_sprite, 1.).draw_sprite(*texture, petals.uv, flower.color);
+        let points = place(&FLOWER_SPRITE, 1.);
+        let regrowth = flower.regrowth(clock, settings);
+        if flower.collected && regrowth > 0. {
+            // The nectar swells back up from the middle
+            let color = Color::new(1., 1., 1., regrowth);
+            place(&FLOWER_SPRITE, regrowth).draw_sprite(
+                *texture,
+                spritesheet::FLOWER_FRAMES[1].uv,
+                color,
+            );
+        }
         if !flower.collected {
             points.draw_sprite(*texture, spritesheet::FLOWER_FRAMES[1].uv, WHITE);
+            // Power-ups pulse, so they are hard to miss
+            if let Some(power_up) = flower.power_up {
+                let pulse = 1.3 + 0.1 * (clock.time * 6.).sin() as f32;
+                draw_circle_lines(
+                    pos.x,
+                    pos.y,
+                    flower.radius * pulse,
+                    flower.radius / 6.,
+                    power_up.color(),
+                );
+            }
         }
         #[cfg(feature = "wireframes")]
         {
@@ -145,12 +701,205 @@ fn draw_flower(
     }
 }
 
+/// Name the flowers that were just picked, floating up and fading away
+#[system]
+fn draw_pickings(
+    #[resource] pickings: &Pickings,
+    #[resource] flora: &Flora,
+    #[resource] camera: &crate::camera::Camera,
+    #[resource] clock: &GameClock,
+    #[resource] settings: &Settings,
+) {
+    let Rect { w, h, .. } = camera.rect;
+    let font = Font::default();
+    let font_size = (settings.font_size / 20.) as u16;
+    let font_scale = vec2(w, h).length() / settings.max_zoom;
+    for picking in &pickings.0 {
+        let age = ((clock.time - picking.time) / PICKING_TIME) as f32;
+        let text = &format!("{} +{}", flora.get(picking.species).name, picking.nectar);
+        let TextDimensions { width, .. } = measure_text(text, Some(font), font_size, font_scale);
+        let params = TextParams {
+            font,
+            font_size,
+            font_scale,
+            font_scale_aspect: 1.0,
+            color: Color::new(1., 1., 1., 1. - age),
+        };
+        let x = picking.pos.x - width / 2.;
+        let y = picking.pos.y - PICKING_RISE * age;
+        draw_text_ex(text, x, y, params);
+    }
+}
+
 /// Make reasonable flower colors, basically green<=blue&red, and at least one maxed channel.
-fn rand_flower_color() -> Color {
-    let r = gen_range(0f32, 1.);
-    let b = gen_range(0., 1.);
-    let g = gen_range(0., r.max(b));
+pub fn rand_flower_color(rng: &mut Rng) -> Color {
+    let r = rng.gen_range(0., 1.);
+    let b = rng.gen_range(0., 1.);
+    let g = rng.gen_range(0., r.max(b));
     // scale them all so that the max channel is 1.
     let [r, g, b]: [f32; 3] = (vec3(r, g, b) / r.max(b)).into();
     Color::new(r, g, b, 1.)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn infinite() -> Settings {
+        Settings {
+            seed: 42,
+            infinite_meadow: true,
+            ..Settings::default()
+        }
+    }
+
+    fn keys(keys: &[ChunkKey]) -> BTreeSet<ChunkKey> {
+        keys.iter().copied().collect()
+    }
+
+    /// Plant a meadow that goes on forever and stream in each of `loads` in
+    /// turn, keeping only what is wanted each time
+    fn streamed(loads: &[&[ChunkKey]]) -> (World, Meadow) {
+        let settings = infinite();
+        let flora = Flora::load();
+        let mut world = World::default();
+        let mut rng = Rng::new(settings.seed);
+        let mut meadow = Meadow::new(&mut world, &settings, &flora, &mut rng);
+        for load in loads {
+            let wanted = keys(load);
+            meadow.stream(&mut world, &wanted, &wanted, &settings, &flora);
+        }
+        (world, meadow)
+    }
+
+    fn flowers(world: &World, meadow: &Meadow) -> Vec<(ChunkKey, Flower)> {
+        meadow
+            .flower_entities()
+            .map(|(key, entity)| {
+                let entry = world.entry_ref(entity).expect("flower missing");
+                (
+                    key,
+                    *entry
+                        .get_component::<Flower>()
+                        .expect("flower data missing"),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chunks_grow_the_same_in_any_order() {
+        let (world, meadow) = streamed(&[&[(0, 0), (1, -1)]]);
+        let (other_world, other_meadow) = streamed(&[&[(3, 3)], &[(1, -1)], &[(0, 0), (1, -1)]]);
+        assert!(!flowers(&world, &meadow).is_empty());
+        assert_eq!(meadow.grounds(), other_meadow.grounds());
+        assert_eq!(
+            flowers(&world, &meadow),
+            flowers(&other_world, &other_meadow)
+        );
+    }
+
+    #[test]
+    fn dug_up_chunks_leave_the_world() {
+        let (mut world, mut meadow) = streamed(&[&[(0, 0), (0, 1)]]);
+        let settings = infinite();
+        let wanted = keys(&[(0, 1)]);
+        meadow.stream(&mut world, &wanted, &wanted, &settings, &Flora::load());
+        assert_eq!(meadow.grounds().len(), 1);
+        assert!(meadow.flower_entities().all(|(key, _)| key == (0, 1)));
+        assert!(meadow.web_entities().all(|(key, _)| key == (0, 1)));
+        let flowers = <&Flower>::query().iter(&world).count();
+        let webs = <&Web>::query().iter(&world).count();
+        assert_eq!(flowers, meadow.flower_entities().count());
+        assert_eq!(webs, meadow.web_entities().count());
+    }
+
+    #[test]
+    fn picked_flowers_stay_picked_when_dug_up() {
+        let (mut world, mut meadow) = streamed(&[&[(0, 0)]]);
+        let settings = infinite();
+        let flora = Flora::load();
+        let (_, entity) = meadow.flower_entities().nth(3).expect("too few flowers");
+        let mut entry = world.entry(entity).expect("flower missing");
+        let flower = entry
+            .get_component_mut::<Flower>()
+            .expect("flower data missing");
+        flower.collected = true;
+        flower.collected_at = 12.;
+        let picked = *flower;
+
+        let elsewhere = keys(&[(5, 5)]);
+        meadow.stream(&mut world, &elsewhere, &elsewhere, &settings, &flora);
+        assert_eq!(meadow.picked(), vec![((0, 0), vec![(3, 12.)])]);
+        let back = keys(&[(0, 0)]);
+        meadow.stream(&mut world, &back, &back, &settings, &flora);
+        assert!(meadow.picked().is_empty());
+        let flowers = flowers(&world, &meadow);
+        assert_eq!(flowers[3], ((0, 0), picked));
+        assert_eq!(
+            flowers
+                .iter()
+                .filter(|(_, flower)| flower.collected)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn overhanging_flowers_are_found_from_next_door() {
+        let settings = infinite();
+        let (_, planted) = streamed(&[&[(0, 0), (1, 0)]]);
+        let edge = settings.chunk_size();
+        let flower = Flower {
+            species: 0,
+            color: WHITE,
+            radius: 30.,
+            collected: false,
+            collected_at: 0.,
+            power_up: None,
+        };
+        let mut world = World::default();
+        let meadow = Meadow::plant(
+            &mut world,
+            &settings,
+            planted.size,
+            planted.grounds(),
+            vec![((1, 0), flower, Position::from(vec2(edge + 10., 500.)))],
+            Vec::new(),
+            Vec::new(),
+        );
+        // Just the chunk to the left, but the petals reach into it
+        let rect = Rect::new(edge - 25., 490., 20., 20.);
+        assert_eq!(meadow.chunks_around(rect).collect::<Vec<_>>(), vec![(0, 0)]);
+        assert_eq!(meadow.flowers_within(rect).count(), 1);
+    }
+
+    #[test]
+    fn picked_flowers_grow_back_in_time() {
+        let settings = Settings {
+            regrowth_time: 20.,
+            ..Settings::default()
+        };
+        let at = |time| GameClock {
+            time,
+            ..GameClock::default()
+        };
+        let mut flower = Flower {
+            species: 0,
+            color: WHITE,
+            radius: 30.,
+            collected: false,
+            collected_at: 0.,
+            power_up: None,
+        };
+        assert_eq!(flower.regrowth(&at(5.), &settings), 1.);
+        flower.collect(&at(10.));
+        assert_eq!(flower.regrowth(&at(20.), &settings), 0.5);
+        assert_eq!(flower.regrowth(&at(40.), &settings), 1.);
+        let never = Settings {
+            regrowth_time: 0.,
+            ..Settings::default()
+        };
+        assert_eq!(flower.regrowth(&at(1000.), &never), 0.);
+    }
+}
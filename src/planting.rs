@@ -0,0 +1,224 @@
+//! Planting the flowers.
+//!
+//! Where the seeds land. Thrown about, spaced out, in patches, or in nice
+//! neat rows.
+
+use std::f32::consts::TAU;
+
+use crate::{ground::Ground, meadow::rand_pos, prelude::*, settings::FlowerLayout};
+
+/// How many places a flower tries before settling for bad ground
+const ROOTING_TRIES: usize = 20;
+/// How many spots around each flower Poisson-disk sampling tries before
+/// giving up on it
+const POISSON_TRIES: usize = 30;
+/// How many flowers grow in each patch
+const CLUSTER_SIZE: usize = 25;
+/// How far a patch spreads from its middle
+const CLUSTER_SPREAD: f32 = 250.;
+/// How far apart rows are, in flower sizes
+const ROW_SPACING: f32 = 5.;
+/// How far a flower strays from its place in the row, in gaps between
+/// flowers
+const ROW_JITTER: f32 = 0.1;
+
+/// Where up to `count` flowers, no bigger than `radius`, grow in a meadow of
+/// `size`
+pub fn sow(
+    layout: FlowerLayout,
+    count: usize,
+    size: Vec2,
+    ground: &Ground,
+    radius: f32,
+    rng: &mut Rng,
+) -> Vec<Vec2> {
+    match layout {
+        FlowerLayout::Scattered => scattered(count, size, ground, rng),
+        FlowerLayout::PoissonDisk => poisson_disk(count, size, ground, radius, rng),
+        FlowerLayout::Clusters => clusters(count, size, ground, rng),
+        FlowerLayout::Rows => rows(count, size, ground, radius, rng),
+    }
+}
+
+/// Flowers would rather grow in the grass than in the dirt, and can not grow
+/// in ponds or on rocks at all
+fn takes_root(pos: Vec2, ground: &Ground, rng: &mut Rng) -> bool {
+    rng.gen_range(0., 1.) < ground.tile_at(pos).fertility()
+}
+
+fn rand_index(len: usize, rng: &mut Rng) -> usize {
+    (rng.gen_range(0., len as f32) as usize).min(len - 1)
+}
+
+/// Somewhere in the meadow a flower will grow, if one turns up quick enough
+fn rand_root(size: Vec2, ground: &Ground, rng: &mut Rng) -> Vec2 {
+    let mut pos = rand_pos(&size, rng);
+    for _ in 0..ROOTING_TRIES {
+        if takes_root(pos, ground, rng) {
+            break;
+        }
+        pos = rand_pos(&size, rng);
+    }
+    pos
+}
+
+/// Anywhere at all, even on top of each other
+fn scattered(count: usize, size: Vec2, ground: &Ground, rng: &mut Rng) -> Vec<Vec2> {
+    (0..count).map(|_| rand_root(size, ground, rng)).collect()
+}
+
+/// Evenly spread, with no two flowers close enough to overlap. Fills the
+/// meadow (Bridson's algorithm), then thins it out to `count`.
+fn poisson_disk(
+    count: usize,
+    size: Vec2,
+    ground: &Ground,
+    radius: f32,
+    rng: &mut Rng,
+) -> Vec<Vec2> {
+    let spacing = radius * 2.;
+    let cell = spacing / 2f32.sqrt();
+    let columns = (size.x / cell).ceil() as usize + 1;
+    let rows = (size.y / cell).ceil() as usize + 1;
+    let cell_of = |pos: Vec2| ((pos.x / cell) as usize, (pos.y / cell) as usize);
+    let mut grid: Vec<Option<usize>> = vec![None; columns * rows];
+    let mut flowers = vec![rand_root(size, ground, rng)];
+    let (x, y) = cell_of(flowers[0]);
+    grid[y * columns + x] = Some(0);
+    let mut active = vec![0];
+    while !active.is_empty() {
+        let n = rand_index(active.len(), rng);
+        let from = flowers[active[n]];
+        let found = (0..POISSON_TRIES).find_map(|_| {
+            let angle = rng.gen_range(0., TAU);
+            let pos = from + vec2(angle.cos(), angle.sin()) * rng.gen_range(spacing, spacing * 2.);
+            if pos.x < 0. || pos.y < 0. || pos.x >= size.x || pos.y >= size.y {
+                return None;
+            }
+            let (x, y) = cell_of(pos);
+            let crowded = (y.saturating_sub(2)..(y + 3).min(rows)).any(|y| {
+                (x.saturating_sub(2)..(x + 3).min(columns)).any(|x| {
+                    grid[y * columns + x]
+                        .is_some_and(|other| flowers[other].distance(pos) < spacing)
+                })
+            });
+            (!crowded && takes_root(pos, ground, rng)).then_some((pos, x, y))
+        });
+        match found {
+            Some((pos, x, y)) => {
+                grid[y * columns + x] = Some(flowers.len());
+                active.push(flowers.len());
+                flowers.push(pos);
+            }
+            None => {
+                active.swap_remove(n);
+            }
+        }
+    }
+    while flowers.len() > count {
+        let n = rand_index(flowers.len(), rng);
+        flowers.swap_remove(n);
+    }
+    flowers
+}
+
+/// In patches around a few random spots
+fn clusters(count: usize, size: Vec2, ground: &Ground, rng: &mut Rng) -> Vec<Vec2> {
+    let patches: Vec<Vec2> = (0..count.div_ceil(CLUSTER_SIZE))
+        .map(|_| rand_root(size, ground, rng))
+        .collect();
+    (0..count)
+        .map(|n| {
+            let middle = patches[n % patches.len()];
+            let spot = |rng: &mut Rng| {
+                // The square root spreads them evenly over the patch
+                let angle = rng.gen_range(0., TAU);
+                let distance = rng.gen_range(0., 1.).sqrt() * CLUSTER_SPREAD;
+                (middle + vec2(angle.cos(), angle.sin()) * distance).clamp(Vec2::ZERO, size)
+            };
+            let mut pos = spot(rng);
+            for _ in 0..ROOTING_TRIES {
+                if takes_root(pos, ground, rng) {
+                    break;
+                }
+                pos = spot(rng);
+            }
+            pos
+        })
+        .collect()
+}
+
+/// In rows across the whole meadow, with gaps wherever the ground is no good
+fn rows(count: usize, size: Vec2, ground: &Ground, radius: f32, rng: &mut Rng) -> Vec<Vec2> {
+    let num_rows = ((size.y / (radius * ROW_SPACING)) as usize).max(1);
+    let per_row = count.div_ceil(num_rows);
+    let row_gap = size.y / num_rows as f32;
+    let gap = size.x / per_row.max(1) as f32;
+    (0..count)
+        .filter_map(|n| {
+            let (row, column) = (n / per_row, n % per_row);
+            let jitter = vec2(rng.gen_range(-1., 1.), rng.gen_range(-1., 1.)) * gap * ROW_JITTER;
+            let pos = vec2((column as f32 + 0.5) * gap, (row as f32 + 0.5) * row_gap) + jitter;
+            let pos = pos.clamp(Vec2::ZERO, size);
+            takes_root(pos, ground, rng).then_some(pos)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: f32 = 2000.;
+    const HEIGHT: f32 = 1500.;
+
+    fn sown(layout: FlowerLayout, count: usize, radius: f32) -> Vec<Vec2> {
+        let settings = Settings::default();
+        let mut rng = Rng::new(42);
+        let size = vec2(WIDTH, HEIGHT);
+        let ground = Ground::new(size, false, &settings, &mut rng);
+        sow(layout, count, size, &ground, radius, &mut rng)
+    }
+
+    #[test]
+    fn scattered_grows_every_flower() {
+        assert_eq!(sown(FlowerLayout::Scattered, 500, 20.).len(), 500);
+    }
+
+    #[test]
+    fn no_layout_grows_too_many() {
+        for layout in [
+            FlowerLayout::Scattered,
+            FlowerLayout::PoissonDisk,
+            FlowerLayout::Clusters,
+            FlowerLayout::Rows,
+        ] {
+            let flowers = sown(layout, 300, 20.);
+            assert!(!flowers.is_empty(), "{:?} grew nothing", layout);
+            assert!(flowers.len() <= 300, "{:?} grew {}", layout, flowers.len());
+            assert!(flowers
+                .iter()
+                .all(|pos| (0. ..=WIDTH).contains(&pos.x) && (0. ..=HEIGHT).contains(&pos.y)));
+        }
+    }
+
+    #[test]
+    fn poisson_disk_flowers_never_overlap() {
+        let radius = 20.;
+        let flowers = sown(FlowerLayout::PoissonDisk, 300, radius);
+        assert_eq!(flowers.len(), 300);
+        for (n, a) in flowers.iter().enumerate() {
+            for b in &flowers[n + 1..] {
+                assert!(a.distance(*b) >= radius * 2., "{} and {} overlap", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn poisson_disk_grows_only_as_many_as_fit() {
+        // Flowers 200 across can not fit more than 10 by 7.5 of them
+        let flowers = sown(FlowerLayout::PoissonDisk, 1000, 100.);
+        assert!(!flowers.is_empty());
+        assert!(flowers.len() < 75, "{} flowers fit", flowers.len());
+    }
+}
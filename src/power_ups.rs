@@ -0,0 +1,157 @@
+//! Power-ups.
+//!
+//! Some flowers have a little something extra in them. Drink up, but it
+//! won't last!
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How much faster a speed boost makes the bee
+const SPEED_BOOST: f32 = 1.5;
+/// How far past the bee's hitbox a nectar magnet reaches for flowers
+const MAGNET_REACH: f32 = 100.;
+/// How much bigger a giant bee is
+const GIANT_SCALE: f32 = 2.;
+
+/// What a power-up flower does for the bee that collects it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PowerUp {
+    /// More thrust
+    SpeedBoost,
+    /// Collect flowers from further away
+    NectarMagnet,
+    /// A bigger bee, so a bigger hitbox
+    GiantBee,
+}
+impl PowerUp {
+    const ALL: [PowerUp; 3] = [
+        PowerUp::SpeedBoost,
+        PowerUp::NectarMagnet,
+        PowerUp::GiantBee,
+    ];
+
+    /// Maybe a power-up, for a new flower
+    pub fn rand(settings: &Settings, rng: &mut Rng) -> Option<PowerUp> {
+        if rng.gen_range(0., 100.) >= settings.power_up_chance {
+            return None;
+        }
+        let n = rng.gen_range(0., Self::ALL.len() as f32) as usize;
+        Some(Self::ALL[n.min(Self::ALL.len() - 1)])
+    }
+
+    /// How power-up flowers stand out
+    pub fn color(self) -> Color {
+        match self {
+            PowerUp::SpeedBoost => SKYBLUE,
+            PowerUp::NectarMagnet => MAGENTA,
+            PowerUp::GiantBee => ORANGE,
+        }
+    }
+}
+
+/// The power-ups working on a bee, as when each one wears off on the
+/// [`GameClock`]
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct Effects {
+    speed_boost: f64,
+    nectar_magnet: f64,
+    giant_bee: f64,
+}
+impl Effects {
+    /// Start `power_up`, or keep it going for longer
+    pub fn apply(&mut self, power_up: PowerUp, clock: &GameClock, settings: &Settings) {
+        let until = clock.time + settings.power_up_time as f64;
+        let expiry = match power_up {
+            PowerUp::SpeedBoost => &mut self.speed_boost,
+            PowerUp::NectarMagnet => &mut self.nectar_magnet,
+            PowerUp::GiantBee => &mut self.giant_bee,
+        };
+        *expiry = until;
+    }
+
+    /// How much more thrust than usual the bee has
+    pub fn thrust_scale(&self, clock: &GameClock) -> f32 {
+        if clock.time < self.speed_boost {
+            SPEED_BOOST
+        } else {
+            1.
+        }
+    }
+
+    /// How far beyond its hitbox the bee collects flowers
+    pub fn magnet_reach(&self, clock: &GameClock) -> f32 {
+        if clock.time < self.nectar_magnet {
+            MAGNET_REACH
+        } else {
+            0.
+        }
+    }
+
+    /// How big the bee is right now
+    pub fn bee_size(&self, clock: &GameClock, settings: &Settings) -> f32 {
+        if clock.time < self.giant_bee {
+            settings.bee_size * GIANT_SCALE
+        } else {
+            settings.bee_size
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: f64) -> GameClock {
+        GameClock {
+            time,
+            ..GameClock::default()
+        }
+    }
+
+    #[test]
+    fn effects_wear_off() {
+        let settings = Settings {
+            power_up_time: 10.,
+            ..Settings::default()
+        };
+        let mut effects = Effects::default();
+        effects.apply(PowerUp::SpeedBoost, &at(5.), &settings);
+        effects.apply(PowerUp::GiantBee, &at(5.), &settings);
+        assert_eq!(effects.thrust_scale(&at(14.)), SPEED_BOOST);
+        assert_eq!(
+            effects.bee_size(&at(14.), &settings),
+            settings.bee_size * GIANT_SCALE
+        );
+        assert_eq!(effects.magnet_reach(&at(14.)), 0.);
+        assert_eq!(effects.thrust_scale(&at(15.)), 1.);
+        assert_eq!(effects.bee_size(&at(15.), &settings), settings.bee_size);
+    }
+
+    #[test]
+    fn another_power_up_keeps_it_going() {
+        let settings = Settings {
+            power_up_time: 10.,
+            ..Settings::default()
+        };
+        let mut effects = Effects::default();
+        effects.apply(PowerUp::NectarMagnet, &at(0.), &settings);
+        effects.apply(PowerUp::NectarMagnet, &at(8.), &settings);
+        assert_eq!(effects.magnet_reach(&at(12.)), MAGNET_REACH);
+        assert_eq!(effects.magnet_reach(&at(18.)), 0.);
+    }
+
+    #[test]
+    fn power_ups_only_grow_as_often_as_asked() {
+        let mut rng = Rng::new(3);
+        let never = Settings {
+            power_up_chance: 0.,
+            ..Settings::default()
+        };
+        assert!((0..1000).all(|_| PowerUp::rand(&never, &mut rng).is_none()));
+        let always = Settings {
+            power_up_chance: 100.,
+            ..Settings::default()
+        };
+        assert!((0..1000).all(|_| PowerUp::rand(&always, &mut rng).is_some()));
+    }
+}
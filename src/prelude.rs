@@ -1,18 +1,18 @@
 //! Where to stick the common vocabulary
 pub use crate::settings::Settings;
-use legion::Entity;
-pub use legion::{system, systems::Builder, Resources, Schedule, World};
+pub use legion::{system, Resources, Schedule, World};
 pub use macroquad::prelude::*;
 use parry2d::{
     math::{Point, Real},
     na::Point2,
     shape::Polyline,
 };
+use serde::{Deserialize, Serialize};
 use std::ops::Index;
 pub use std::time::Duration;
 
 /// A location
-#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
 pub struct Position(pub Vec2);
 impl From<Vec2> for Position {
     fn from(position: Vec2) -> Self {
@@ -20,8 +20,18 @@ impl From<Vec2> for Position {
     }
 }
 
+/// Where something was before the latest tick, so drawing can interpolate
+/// between ticks
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct PreviousPosition(pub Vec2);
+impl From<Vec2> for PreviousPosition {
+    fn from(position: Vec2) -> Self {
+        PreviousPosition(position)
+    }
+}
+
 // A velocity
-#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
 pub struct Velocity(pub Vec2);
 impl From<Vec2> for Velocity {
     fn from(velocity: Vec2) -> Self {
@@ -29,24 +39,111 @@ impl From<Vec2> for Velocity {
     }
 }
 
+/// How the player is steering the bee
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Steering {
+    /// Head for this spot in the meadow
+    Destination(Vec2),
+    /// Push this way, as a fraction of the max thrust
+    Thrust(Vec2),
+}
+
+/// How many people can play at once
+pub const MAX_PLAYERS: usize = 2;
+
+/// One player's inputs
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct PlayerInputs {
+    pub steering: Option<Steering>,
+    /// A spot to head for once the bee gets where it is going. Only lasts for
+    /// a single tick.
+    pub waypoint: Option<Vec2>,
+}
+
 // Inputs. Loaded as a resource
-#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
 pub struct Inputs {
-    pub mouse_click: Option<Vec2>,
+    /// Indexed by [`Player`]
+    pub players: [PlayerInputs; MAX_PLAYERS],
 }
+/// Where we are in the show. Loaded as a resource
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameState {
+    /// The title screen, before the round starts
+    Title,
+    /// The round is on
+    Playing,
+    /// The round is on hold, but still on screen
+    Paused,
+    /// The round is over, here is how it went
+    Results,
+    /// Done with this round, set up the next one
+    NewRound,
+    /// Done with the game
+    Quit,
+}
+
 /// Clock. Loaded as a resource
-#[derive(Clone, Copy, Debug, PartialEq, Default)]
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
 pub struct GameClock {
     pub time: f64,
     pub tick: Duration,
+    /// How far we are between the previous tick and the next one, in `0..1`.
+    /// Only useful for drawing.
+    pub alpha: f32,
 }
+impl GameClock {
+    /// Where to draw something that moved from `previous` to `current` in
+    /// the latest tick.
+    pub fn interpolate(&self, previous: &PreviousPosition, current: &Position) -> Vec2 {
+        previous.0.lerp(current.0, self.alpha)
+    }
 
-/// The bees resource
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct TheBee {
-    pub entity: Entity,
+    /// Move the clock forward by `tick`, without looking at the wall clock.
+    pub fn advance(&mut self, tick: Duration) {
+        self.time += tick.as_secs_f64();
+        self.tick = tick;
+    }
 }
 
+/// Random numbers. Loaded as a resource, seeded from [`Settings::seed`] so
+/// the same seed always grows the same meadow.
+pub struct Rng(oorandom::Rand32);
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(oorandom::Rand32::new(seed))
+    }
+
+    /// A number in `low..high`
+    pub fn gen_range(&mut self, low: f32, high: f32) -> f32 {
+        low + (high - low) * self.0.rand_float()
+    }
+}
+/// So the same dice can be handed to crates that roll their own, like `wfc`
+impl rand_core::RngCore for Rng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.rand_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Marks a bee someone is flying, and which player they are, counting from
+/// 0. Every other bee flies itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Player(pub usize);
+
 /// A quadrilateral of points. Points appear clockwise.
 pub struct Quad(pub [Vec2; 4]);
 
@@ -131,7 +228,6 @@ impl Quad {
         let vertices: Vec<_> = {
             use macroquad::models::Vertex;
             (0..4)
-                .into_iter()
                 .map(|n| Vertex {
                     position: self[n].extend(0.),
                     uv: uv[n],
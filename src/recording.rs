@@ -0,0 +1,69 @@
+//! Recordings of a game.
+//!
+//! Everything that happened, tick by tick, so we can watch it all again
+//! exactly the way it went.
+
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Bump this whenever the recording format, or anything that changes how a
+/// recording plays back, changes.
+const RECORDING_VERSION: u32 = 1;
+
+/// The settings (and so the seed) of a game and the inputs for every tick of
+/// it. Inputs are run-length encoded since they mostly repeat.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Recording {
+    version: u32,
+    pub settings: Settings,
+    inputs: Vec<(u32, Inputs)>,
+}
+
+impl Recording {
+    pub fn new(settings: &Settings) -> Self {
+        Recording {
+            version: RECORDING_VERSION,
+            settings: settings.clone(),
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Add the inputs for the next tick
+    pub fn record(&mut self, inputs: &Inputs) {
+        match self.inputs.last_mut() {
+            Some((count, last)) if last == inputs => *count += 1,
+            _ => self.inputs.push((1, *inputs)),
+        }
+    }
+
+    /// The inputs for every tick, in order
+    pub fn inputs(&self) -> impl Iterator<Item = Inputs> + '_ {
+        self.inputs
+            .iter()
+            .flat_map(|(count, inputs)| std::iter::repeat_n(*inputs, *count as usize))
+    }
+
+    pub fn save(&self, path: &std::path::Path) -> std::io::Result<()> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        bincode::serialize_into(file, self).map_err(into_io_error)
+    }
+
+    pub fn load(path: &std::path::Path) -> std::io::Result<Self> {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let recording: Recording = bincode::deserialize_from(file).map_err(into_io_error)?;
+        if recording.version != RECORDING_VERSION {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "recording version {} but expected {}",
+                    recording.version, RECORDING_VERSION
+                ),
+            ));
+        }
+        Ok(recording)
+    }
+}
+
+fn into_io_error(err: bincode::Error) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+}
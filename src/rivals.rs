@@ -0,0 +1,106 @@
+//! The rivals.
+//!
+//! Other bees want the nectar too. They are not as quick on the uptake as
+//! you, but they never get distracted.
+
+use crate::{
+    bee::{Bee, Waypoints},
+    meadow::{Flower, Meadow},
+    prelude::*,
+    species::Flora,
+};
+use legion::{world::SubWorld, EntityStore as _};
+use serde::{Deserialize, Serialize};
+
+/// Marks a bee the computer is flying
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct Rival {
+    /// When the rival next looks around for a flower, on the game clock
+    next_look: f64,
+}
+
+pub fn roll_call(
+    world: &mut legion::world::World,
+    systems: &mut legion::systems::Builder,
+    resources: &mut legion::systems::Resources,
+) {
+    {
+        let meadow = resources.get::<Meadow>().expect("No meadow");
+        let settings = resources.get::<Settings>().expect("No settings");
+        let mut rng = resources.get_mut::<Rng>().expect("No rng");
+        for _ in 0..settings.num_rivals {
+            let destination = meadow.rand_pos(&mut rng);
+            let pos = meadow.rand_pos(&mut rng);
+            let entity = crate::bee::spawn(
+                world,
+                Bee::new(destination, settings.rival_thrust / 100.),
+                Position::from(pos),
+                Velocity::default(),
+                Waypoints::default(),
+            );
+            world
+                .entry(entity)
+                .expect("Rival missing")
+                .add_component(Rival::default());
+        }
+    }
+    systems.add_system(forage_system());
+}
+
+/// Every so often, head for the nearest flower nobody has got to yet that
+/// there is room for, or back to the hive once there is none.
+#[system(for_each)]
+#[read_component(Flower)]
+#[read_component(Position)]
+#[allow(clippy::too_many_arguments)]
+fn forage(
+    world: &SubWorld,
+    bee: &mut Bee,
+    pos: &Position,
+    rival: &mut Rival,
+    #[resource] meadow: &Meadow,
+    #[resource] flora: &Flora,
+    #[resource] clock: &GameClock,
+    #[resource] settings: &Settings,
+) {
+    if clock.time < rival.next_look {
+        return;
+    }
+    rival.next_look = clock.time + settings.rival_reaction_time as f64;
+    let hive_pos = || {
+        world
+            .entry_ref(meadow.hive)
+            .expect("Hive missing")
+            .get_component::<Position>()
+            .expect("Hive missing pos")
+            .0
+    };
+    // Full up, so home to the hive
+    if bee.is_full(settings) {
+        bee.set_destination(hive_pos());
+        return;
+    }
+    // Only the flowers it has room for
+    let room = (settings.pollen_capacity as u32).saturating_sub(bee.pollen());
+    let nearest = meadow.nearest_flower(pos.0, |flower| {
+        let entry = world.entry_ref(flower).expect("flower disappeared");
+        let flower = entry
+            .get_component::<Flower>()
+            .expect("Flower missing flower data");
+        !flower.collected && flora.get(flower.species).nectar <= room
+    });
+    match nearest {
+        Some(flower) => {
+            let flower_pos = world
+                .entry_ref(flower)
+                .expect("flower disappeared")
+                .get_component::<Position>()
+                .expect("Flower missing pos")
+                .0;
+            bee.set_destination(flower_pos);
+        }
+        // Nothing left to pick, so bank whatever it has
+        None if bee.pollen() > 0 => bee.set_destination(hive_pos()),
+        None => {}
+    }
+}
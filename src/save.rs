@@ -0,0 +1,112 @@
+//! Saved games.
+//!
+//! Put the meadow away for later, and get it back out just the way we left
+//! it.
+
+use crate::{
+    bee::{Bee, Waypoints},
+    birds::Bird,
+    enemies::Enemy,
+    flytrap::Flytrap,
+    ground::Ground,
+    meadow::{ChunkKey, Flower, Picked},
+    power_ups::Effects,
+    prelude::*,
+    rivals::Rival,
+    species::Flora,
+    storage,
+    web::Web,
+};
+use serde::{Deserialize, Serialize};
+
+/// Bump this whenever anything in a [`SaveGame`] changes
+const SAVE_VERSION: u32 = 1;
+
+/// Where the game is kept in [`storage`]
+const SAVE_KEY: &str = "save";
+
+/// A bee, its power-ups, and whether a player or a rival is flying it
+pub type SavedBee = (
+    Bee,
+    Position,
+    Velocity,
+    Waypoints,
+    Effects,
+    Option<Player>,
+    Option<Rival>,
+);
+
+/// Everything needed to pick a game back up where it was left.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SaveGame {
+    version: u32,
+    pub settings: Settings,
+    pub clock: GameClock,
+    pub meadow_size: Vec2,
+    /// The ground under each chunk of the meadow that was planted
+    pub grounds: Vec<(ChunkKey, Ground)>,
+    /// Every bee, and who is flying it
+    pub bees: Vec<SavedBee>,
+    /// Every flower, in the chunk it grew in
+    pub flowers: Vec<(ChunkKey, Flower, Position)>,
+    pub flytraps: Vec<(Flytrap, Position)>,
+    /// Every web, in the chunk it was spun in
+    pub webs: Vec<(ChunkKey, Web, Position)>,
+    /// The flowers picked in chunks that were dug up
+    pub picked: Vec<(ChunkKey, Picked)>,
+    pub birds: Vec<(Bird, Enemy, Position, Velocity)>,
+}
+
+impl SaveGame {
+    /// An empty meadow, for the cast to be filled in
+    pub fn new(
+        settings: Settings,
+        clock: GameClock,
+        meadow_size: Vec2,
+        grounds: Vec<(ChunkKey, Ground)>,
+    ) -> Self {
+        SaveGame {
+            version: SAVE_VERSION,
+            settings,
+            clock,
+            meadow_size,
+            grounds,
+            bees: Vec::new(),
+            flowers: Vec::new(),
+            flytraps: Vec::new(),
+            webs: Vec::new(),
+            picked: Vec::new(),
+            birds: Vec::new(),
+        }
+    }
+
+    /// The saved game, if there is one we can still read.
+    pub fn fetch() -> Option<Self> {
+        let save: SaveGame = ron::from_str(&storage::load(SAVE_KEY)?).ok()?;
+        if save.version != SAVE_VERSION {
+            return None;
+        }
+        // The species may have changed since, even if the format has not
+        let flora = Flora::load();
+        if !save
+            .flowers
+            .iter()
+            .all(|(_, flower, _)| flora.has(flower.species))
+        {
+            return None;
+        }
+        Some(save)
+    }
+
+    /// Keep this game, replacing any other saved game.
+    pub fn store(&self) -> std::io::Result<()> {
+        let save = ron::to_string(self)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        storage::save(SAVE_KEY, &save)
+    }
+
+    /// Throw out the saved game, if there is one.
+    pub fn discard() -> std::io::Result<()> {
+        storage::remove(SAVE_KEY)
+    }
+}
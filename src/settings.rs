@@ -1,41 +1,338 @@
-use std::ops::Range;
+use std::{ops::Range, time::Duration};
 
 use macroquad::prelude::*;
-#[derive(Debug, Clone)]
-pub struct Settings {
-    pub quit: bool,
-    pub restart: bool,
+use serde::{Deserialize, Serialize};
+use settings_derive::Console;
+
+/// Where the settings are kept in [`storage`](crate::storage)
+const SETTINGS_KEY: &str = "settings";
+
+/// The tick rates the simulation can run at
+const TICKS_PER_SECOND: Range<f32> = 10.0..240.0;
+/// The longest frames we can be asked to catch up with, in milliseconds
+const MAX_FRAME_TIME: Range<f32> = 16.0..1000.0;
+/// The chunk sizes an infinite meadow can grow in
+const CHUNK_SIZE: Range<f32> = 100.0..10000.0;
 
+/// `value` kept inside `range`, or `fallback` if it is not a number at all
+fn sane(value: f32, range: Range<f32>, fallback: f32) -> f32 {
+    if value.is_finite() {
+        value.clamp(range.start, range.end)
+    } else {
+        fallback
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Console)]
+#[serde(default)]
+pub struct Settings {
+    #[console(
+        label = "Seed",
+        tooltip = "The same seed grows the same meadow. 0 for a new one every round."
+    )]
+    pub seed: u64,
+    #[console(label = "Players", min = 1, max = 2, group = "Game")]
+    pub num_players: usize,
+    #[console(label = "Player 1 controls", group = "Game")]
+    pub control_scheme: ControlScheme,
+    #[console(
+        label = "Player 2 controls",
+        tooltip = "The mouse and touch only fly player 1",
+        group = "Game"
+    )]
+    pub second_control_scheme: ControlScheme,
+    #[console(label = "Ticks per second", min = 10, max = 240, group = "Game")]
+    pub ticks_per_second: f32,
+    #[console(label = "Max frame time (ms)", min = 16, max = 1000, group = "Game")]
+    pub max_frame_time: f32,
+    #[console(label = "Round time (s)", min = 10, max = 600, group = "Game")]
+    pub round_time: f32,
+    #[console(
+        label = "Endless",
+        tooltip = "No time limit. Best with flowers that regrow.",
+        group = "Game"
+    )]
+    pub endless: bool,
+    #[console(
+        label = "Meadow height",
+        min = 1,
+        max = 200,
+        step = 0.1,
+        group = "Meadow"
+    )]
     pub meadow_height: f32,
+    #[console(
+        label = "Meadow width",
+        min = 1,
+        max = 200,
+        step = 0.1,
+        group = "Meadow"
+    )]
     pub meadow_width: f32,
+    #[console(
+        label = "Num flowers",
+        min = 1,
+        max = 1000,
+        tooltip = "In tens of flowers. Layouts that keep them apart grow as many as fit, up to this.",
+        group = "Meadow"
+    )]
     pub num_flowers: usize,
+    #[console(
+        label = "Flower size",
+        min = 5,
+        max = 200,
+        step = 0.1,
+        group = "Meadow"
+    )]
     pub flower_size: Range<f32>,
+    #[console(
+        label = "Regrowth time (s)",
+        min = 0,
+        max = 600,
+        tooltip = "How long until a flower has nectar again. 0 for never.",
+        group = "Meadow"
+    )]
+    pub regrowth_time: f32,
+    #[console(label = "Flower layout", group = "Meadow")]
+    pub flower_layout: FlowerLayout,
+    #[console(
+        label = "Infinite meadow",
+        tooltip = "Explore forever. The meadow grows a chunk at a time as you fly.",
+        group = "Meadow"
+    )]
+    pub infinite_meadow: bool,
+    #[console(
+        label = "Chunk size",
+        min = 100,
+        max = 10000,
+        tooltip = "How much of an infinite meadow grows at a time",
+        group = "Meadow"
+    )]
+    pub chunk_size: f32,
 
+    #[console(label = "Mass", min = 0.1, max = 100, step = 0.01, group = "Bee")]
     pub mass: f32,
+    #[console(label = "Max thrust", min = 1, max = 1000, group = "Bee")]
     pub max_thrust: f32,
+    #[console(label = "Wind resistance", min = 0, max = 1000, group = "Bee")]
     pub wind_resistance: f32,
+    #[console(
+        label = "Wind strength",
+        min = 0,
+        max = 1000,
+        tooltip = "How fast the strongest gusts blow",
+        group = "Meadow"
+    )]
+    pub wind_strength: f32,
+    #[console(
+        label = "Energy drain (%/s)",
+        min = 0,
+        max = 100,
+        step = 0.1,
+        tooltip = "At max thrust",
+        group = "Bee"
+    )]
+    pub energy_drain: f32,
+    #[console(
+        label = "Nectar energy (%)",
+        min = 0,
+        max = 100,
+        step = 0.1,
+        tooltip = "For each unit of nectar. The only way to get energy back.",
+        group = "Bee"
+    )]
+    pub nectar_energy: f32,
+    #[console(label = "Pollen capacity", min = 1, max = 100, group = "Bee")]
+    pub pollen_capacity: usize,
+    #[console(label = "Hive size", min = 10, max = 1000, group = "Meadow")]
+    pub hive_size: f32,
+    #[console(label = "Flytraps", min = 0, max = 100, group = "Meadow")]
+    pub num_flytraps: usize,
+    #[console(
+        label = "Flytrap hold (s)",
+        min = 0,
+        max = 30,
+        step = 0.1,
+        group = "Meadow"
+    )]
+    pub flytrap_hold_time: f32,
+    #[console(
+        label = "Flytrap bite",
+        min = 0,
+        max = 100,
+        tooltip = "How much pollen a flytrap takes",
+        group = "Meadow"
+    )]
+    pub flytrap_bite: u32,
+    #[console(label = "Ground tile size", min = 10, max = 1000, group = "Meadow")]
+    pub tile_size: f32,
+    #[console(label = "Webs", min = 0, max = 100, group = "Meadow")]
+    pub num_webs: usize,
+    #[console(label = "Web size", min = 10, max = 1000, group = "Meadow")]
+    pub web_size: f32,
+    #[console(
+        label = "Web drag",
+        min = 0,
+        max = 10000,
+        tooltip = "Wind resistance on top of the usual, for a bee in a web",
+        group = "Meadow"
+    )]
+    pub web_drag: f32,
+    #[console(label = "Spider speed", min = 0, max = 1000, group = "Meadow")]
+    pub spider_speed: f32,
+    #[console(label = "Meadow color", group = "Meadow")]
+    #[serde(with = "ColorDef")]
     pub meadow_color: Color,
+    #[console(label = "Animation speed", min = 0, max = 100, group = "Bee")]
     pub animation_speed: f32,
+    #[console(label = "Bee size", min = 10, max = 1000, group = "Bee")]
     pub bee_size: f32,
+    #[console(label = "Max zoom", min = 50, max = 2000, group = "Camera")]
     pub max_zoom: f32,
+    #[console(label = "Velocity zoom", min = 0, max = 1000, group = "Camera")]
     pub velocity_zoom: f32,
 
+    #[console(
+        label = "Chance (%)",
+        min = 0,
+        max = 100,
+        step = 0.1,
+        tooltip = "That a flower has a power-up",
+        group = "Power-ups"
+    )]
+    pub power_up_chance: f32,
+    #[console(label = "Duration (s)", min = 1, max = 120, group = "Power-ups")]
+    pub power_up_time: f32,
+
+    #[console(label = "Rivals", min = 0, max = 20, group = "Rivals")]
+    pub num_rivals: usize,
+    #[console(
+        label = "Reaction time (s)",
+        min = 0,
+        max = 5,
+        step = 0.01,
+        tooltip = "How long rivals take to notice a flower is gone",
+        group = "Rivals"
+    )]
+    pub rival_reaction_time: f32,
+    #[console(
+        label = "Thrust (%)",
+        min = 10,
+        max = 200,
+        tooltip = "Of the max thrust",
+        group = "Rivals"
+    )]
+    pub rival_thrust: f32,
+
+    #[console(label = "Birds", min = 0, max = 20, group = "Birds")]
+    pub num_birds: usize,
+    #[console(label = "Bird size", min = 10, max = 2000, group = "Birds")]
+    pub bird_size: f32,
+    #[console(label = "Bird speed", min = 0, max = 1000, group = "Birds")]
+    pub bird_speed: f32,
+    #[console(
+        label = "Bird agility",
+        min = 0,
+        max = 5000,
+        tooltip = "How fast a bird can turn",
+        group = "Birds"
+    )]
+    pub bird_agility: f32,
+    #[console(label = "Bird sight", min = 0, max = 5000, group = "Birds")]
+    pub bird_sight: f32,
+    #[console(
+        label = "Bird bite",
+        min = 0,
+        max = 100,
+        tooltip = "How much pollen a bird knocks out of a bee it catches",
+        group = "Birds"
+    )]
+    pub bird_bite: u32,
+    #[console(
+        label = "Bird rest (s)",
+        min = 0,
+        max = 60,
+        step = 0.1,
+        group = "Birds"
+    )]
+    pub bird_rest_time: f32,
+    #[console(label = "Score font size", min = 10, max = 1000, group = "Score")]
     pub font_size: f32,
+    #[console(label = "Score X offset", min = 0, max = 1000, group = "Score")]
     pub score_x_offset: f32,
+    #[console(label = "Score Y offset", min = 0, max = 1000, group = "Score")]
     pub score_y_offset: f32,
 }
+
+/// How the player flies the bee
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ControlScheme {
+    /// Hold the button where the bee should go
+    Mouse,
+    /// WASD or the arrow keys push the bee around
+    Keyboard,
+    /// The left stick pushes the bee around. Needs the `gamepad` feature.
+    Gamepad,
+}
+
+/// How the flowers are planted in the meadow
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlowerLayout {
+    /// Anywhere at all, even on top of each other
+    Scattered,
+    /// Spread out evenly, never overlapping. Only as many as fit.
+    PoissonDisk,
+    /// In patches
+    Clusters,
+    /// In rows, like a farmer would, missing any that land on bad ground
+    Rows,
+}
+
+/// So serde can see inside macroquad's colors
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Color")]
+pub struct ColorDef {
+    r: f32,
+    g: f32,
+    b: f32,
+    a: f32,
+}
+
 impl Default for Settings {
     fn default() -> Self {
         Settings {
-            quit: false,
-            restart: false,
+            seed: 0,
+            num_players: 1,
+            control_scheme: ControlScheme::Mouse,
+            second_control_scheme: ControlScheme::Keyboard,
+            ticks_per_second: 60.0,
+            max_frame_time: 250.0,
+            round_time: 120.0,
+            endless: false,
             meadow_height: 30.0,
             meadow_width: 30.0,
             num_flowers: 100,
             flower_size: 30.0..35.0,
+            regrowth_time: 0.0,
+            flower_layout: FlowerLayout::Scattered,
+            infinite_meadow: false,
+            chunk_size: 2000.0,
             mass: 1.0,
             max_thrust: 100.0,
             wind_resistance: 70.0,
+            wind_strength: 60.0,
+            energy_drain: 4.0,
+            nectar_energy: 5.0,
+            pollen_capacity: 10,
+            hive_size: 150.0,
+            num_flytraps: 10,
+            flytrap_hold_time: 3.0,
+            flytrap_bite: 3,
+            tile_size: 200.0,
+            num_webs: 5,
+            web_size: 150.0,
+            web_drag: 1000.0,
+            spider_speed: 40.0,
             meadow_color: Color {
                 r: 0.044678375,
                 g: 0.46199453,
@@ -46,6 +343,18 @@ impl Default for Settings {
             bee_size: 200.0,
             max_zoom: 400.0,
             velocity_zoom: 75.0,
+            power_up_chance: 3.0,
+            power_up_time: 10.0,
+            num_rivals: 3,
+            rival_reaction_time: 0.5,
+            rival_thrust: 80.0,
+            num_birds: 1,
+            bird_size: 300.0,
+            bird_speed: 180.0,
+            bird_agility: 200.0,
+            bird_sight: 500.0,
+            bird_bite: 5,
+            bird_rest_time: 8.0,
             font_size: 300.0,
             score_x_offset: 500.0,
             score_y_offset: 500.0,
@@ -53,92 +362,210 @@ impl Default for Settings {
     }
 }
 impl Settings {
-    pub fn want_quit(&mut self) -> bool {
-        let res = self.quit;
-        self.quit = false;
-        res
+    /// The settings we kept last time, with defaults for anything missing.
+    pub fn load() -> Self {
+        match crate::storage::load(SETTINGS_KEY).map(|settings| ron::from_str(&settings)) {
+            Some(Ok(settings)) => settings,
+            Some(Err(err)) => {
+                warn!("Could not read the settings, using the defaults: {}", err);
+                Settings::default()
+            }
+            None => Settings::default(),
+        }
     }
-    pub fn want_restart(&mut self) -> bool {
-        let res = self.restart || self.quit;
-        self.restart = false;
-        res
+
+    /// Keep these settings for next time. Only the console can change them.
+    #[cfg(feature = "console")]
+    pub fn store(&self) -> std::io::Result<()> {
+        let settings = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        crate::storage::save(SETTINGS_KEY, &settings)
+    }
+
+    /// How many bees people are flying
+    pub fn num_players(&self) -> usize {
+        self.num_players.clamp(1, crate::prelude::MAX_PLAYERS)
+    }
+
+    /// How `player` flies their bee
+    pub fn control_scheme(&self, player: usize) -> ControlScheme {
+        match player {
+            0 => self.control_scheme,
+            _ => self.second_control_scheme,
+        }
+    }
+
+    /// These settings, with a seed from the wall clock if none was chosen
+    pub fn seeded(&self) -> Settings {
+        Settings {
+            seed: match self.seed {
+                0 => (miniquad::date::now() * 1000.) as u64,
+                seed => seed,
+            },
+            ..self.clone()
+        }
+    }
+
+    /// How long each fixed tick of the simulation is, at a rate the
+    /// simulation can run at whatever the settings say
+    pub fn tick(&self) -> Duration {
+        let ticks_per_second = sane(
+            self.ticks_per_second,
+            TICKS_PER_SECOND,
+            Settings::default().ticks_per_second,
+        );
+        Duration::from_secs_f32(1. / ticks_per_second)
+    }
+    /// The longest frame we will try to catch up with, anything longer and
+    /// the game just runs slower.
+    pub fn max_frame_time(&self) -> Duration {
+        let max_frame_time = sane(
+            self.max_frame_time,
+            MAX_FRAME_TIME,
+            Settings::default().max_frame_time,
+        );
+        Duration::from_secs_f32(max_frame_time / 1000.)
+    }
+
+    /// How big each chunk of an infinite meadow is. Too small and there
+    /// would be no end of them.
+    pub fn chunk_size(&self) -> f32 {
+        sane(self.chunk_size, CHUNK_SIZE, Settings::default().chunk_size)
     }
 }
 
+/// What the generated `Settings::egui` uses to show each setting
 #[cfg(feature = "console")]
 mod ui {
     use std::ops::Range;
 
-    use super::Settings;
     use egui_macroquad::egui;
     use macroquad::prelude::*;
 
-    impl Settings {
-        pub fn egui(&mut self, ui: &mut egui::Ui) {
-            self.quit.declare_ui("Quit", ui);
-            self.restart.declare_ui("Restart", ui);
-            self.meadow_height.declare_ui("Meadow height", ui);
-            self.meadow_width.declare_ui("Meadow width", ui);
-            self.num_flowers.declare_ui("Num flowers", ui);
-            self.flower_size.declare_ui("Flower size", ui);
-            self.font_size.declare_ui("Score font size", ui);
-            self.score_x_offset.declare_ui("Score X offset", ui);
-            self.score_y_offset.declare_ui("Score Y offset", ui);
-            self.mass.declare_ui("Mass", ui);
-            self.max_thrust.declare_ui("Max thrust", ui);
-            self.wind_resistance.declare_ui("Wind resistance", ui);
-            self.animation_speed.declare_ui("Animation speed", ui);
-            self.bee_size.declare_ui("Bee size", ui);
-            self.max_zoom.declare_ui("Max zoom", ui);
-            self.velocity_zoom.declare_ui("Velocity zoom", ui);
-            self.meadow_color.declare_ui("Meadow color", ui);
+    /// How to show a setting, from its `#[console(...)]` attribute
+    pub struct Tweak {
+        pub label: &'static str,
+        pub tooltip: Option<&'static str>,
+        pub min: Option<f64>,
+        pub max: Option<f64>,
+        pub step: f64,
+    }
+
+    impl Tweak {
+        fn drag_value<'a, Num: egui::emath::Numeric>(
+            &self,
+            value: &'a mut Num,
+        ) -> egui::DragValue<'a> {
+            egui::DragValue::new(value).speed(self.step).clamp_range(
+                self.min.unwrap_or(f64::NEG_INFINITY)..=self.max.unwrap_or(f64::INFINITY),
+            )
+        }
+
+        /// Label the setting, and add whatever the setting needs to edit it.
+        fn row(&self, ui: &mut egui::Ui, add_contents: impl FnOnce(&mut egui::Ui)) {
+            let response = ui
+                .horizontal(|ui| {
+                    ui.label(self.label);
+                    add_contents(ui);
+                })
+                .response;
+            if let Some(tooltip) = self.tooltip {
+                response.on_hover_text(tooltip);
+            }
         }
     }
 
-    trait Config {
-        fn declare_ui(&mut self, label: &str, ui: &mut egui::Ui);
+    pub trait Config {
+        fn declare_ui(&mut self, tweak: &Tweak, ui: &mut egui::Ui);
+    }
+
+    impl Config for bool {
+        fn declare_ui(&mut self, tweak: &Tweak, ui: &mut egui::Ui) {
+            tweak.row(ui, |ui| {
+                ui.checkbox(self, "");
+            });
+        }
     }
 
     impl Config for f32 {
-        fn declare_ui(&mut self, label: &str, ui: &mut egui::Ui) {
-            ui.horizontal(|ui| {
-                ui.label(label);
-                ui.add(egui::DragValue::new(self).clamp_range(1f32..=1000f32));
+        fn declare_ui(&mut self, tweak: &Tweak, ui: &mut egui::Ui) {
+            tweak.row(ui, |ui| {
+                ui.add(tweak.drag_value(self));
             });
         }
     }
 
     impl Config for usize {
-        fn declare_ui(&mut self, label: &str, ui: &mut egui::Ui) {
-            ui.horizontal(|ui| {
-                ui.label(label);
-                ui.add(egui::DragValue::new(self).clamp_range(1..=1000));
+        fn declare_ui(&mut self, tweak: &Tweak, ui: &mut egui::Ui) {
+            tweak.row(ui, |ui| {
+                ui.add(tweak.drag_value(self));
+            });
+        }
+    }
+
+    impl Config for u32 {
+        fn declare_ui(&mut self, tweak: &Tweak, ui: &mut egui::Ui) {
+            tweak.row(ui, |ui| {
+                ui.add(tweak.drag_value(self));
+            });
+        }
+    }
+
+    impl Config for u64 {
+        fn declare_ui(&mut self, tweak: &Tweak, ui: &mut egui::Ui) {
+            tweak.row(ui, |ui| {
+                ui.add(tweak.drag_value(self));
             });
         }
     }
 
     impl Config for Range<f32> {
-        fn declare_ui(&mut self, label: &str, ui: &mut egui::Ui) {
-            ui.horizontal(|ui| {
-                ui.label(label);
-                ui.add(egui::DragValue::new(&mut self.start).clamp_range(1f32..=self.end));
-                ui.add(egui::DragValue::new(&mut self.end).clamp_range(self.start..=1000f32));
+        fn declare_ui(&mut self, tweak: &Tweak, ui: &mut egui::Ui) {
+            tweak.row(ui, |ui| {
+                let Range { start, end } = self;
+                ui.add(
+                    Tweak {
+                        max: Some(*end as f64),
+                        ..*tweak
+                    }
+                    .drag_value(start),
+                );
+                ui.add(
+                    Tweak {
+                        min: Some(*start as f64),
+                        ..*tweak
+                    }
+                    .drag_value(end),
+                );
             });
         }
     }
 
-    impl Config for bool {
-        fn declare_ui(&mut self, label: &str, ui: &mut egui::Ui) {
-            if ui.add(egui::Button::new(label)).clicked() {
-                *self = true;
-            }
+    impl Config for super::ControlScheme {
+        fn declare_ui(&mut self, tweak: &Tweak, ui: &mut egui::Ui) {
+            use super::ControlScheme::*;
+            tweak.row(ui, |ui| {
+                for scheme in [Mouse, Keyboard, Gamepad] {
+                    ui.radio_value(self, scheme, format!("{:?}", scheme));
+                }
+            });
+        }
+    }
+
+    impl Config for super::FlowerLayout {
+        fn declare_ui(&mut self, tweak: &Tweak, ui: &mut egui::Ui) {
+            use super::FlowerLayout::*;
+            tweak.row(ui, |ui| {
+                for layout in [Scattered, PoissonDisk, Clusters, Rows] {
+                    ui.radio_value(self, layout, format!("{:?}", layout));
+                }
+            });
         }
     }
 
     impl Config for Color {
-        fn declare_ui(&mut self, label: &str, ui: &mut egui::Ui) {
-            ui.horizontal(|ui| {
-                ui.label(label);
+        fn declare_ui(&mut self, tweak: &Tweak, ui: &mut egui::Ui) {
+            tweak.row(ui, |ui| {
                 let Color { r, g, b, .. } = *self;
                 let mut hsva = egui::color::Hsva::from_rgb([r, g, b]);
                 egui::color_picker::color_edit_button_hsva(
@@ -152,3 +579,43 @@ mod ui {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_tick_rates_do_not_panic() {
+        for rate in [0., -60., f32::NAN, f32::INFINITY, 1e9] {
+            let settings = Settings {
+                ticks_per_second: rate,
+                max_frame_time: rate,
+                ..Settings::default()
+            };
+            assert!(settings.tick() > Duration::ZERO);
+            assert!(settings.max_frame_time() > Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn each_player_has_their_own_controls() {
+        let settings = Settings {
+            control_scheme: ControlScheme::Mouse,
+            second_control_scheme: ControlScheme::Gamepad,
+            ..Settings::default()
+        };
+        assert_eq!(settings.control_scheme(0), ControlScheme::Mouse);
+        assert_eq!(settings.control_scheme(1), ControlScheme::Gamepad);
+    }
+
+    #[test]
+    fn bad_chunk_sizes_are_kept_sane() {
+        for size in [0., -2000., f32::NAN, f32::INFINITY] {
+            let settings = Settings {
+                chunk_size: size,
+                ..Settings::default()
+            };
+            assert!(CHUNK_SIZE.contains(&settings.chunk_size()));
+        }
+    }
+}
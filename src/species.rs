@@ -0,0 +1,151 @@
+//! The flower species.
+//!
+//! Not all flowers are equal. Some are worth going out of your way for.
+
+use std::ops::Range;
+
+use serde::Deserialize;
+
+use crate::{prelude::*, spritesheet};
+
+/// The species, kept with the other resources so they can be changed without
+/// touching the code
+const SPECIES_RON: &str = include_str!("../resources/species.ron");
+
+/// How a species looks
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum Sprite {
+    /// Lots of thin petals
+    Daisy,
+    /// A few round petals
+    Poppy,
+}
+impl Sprite {
+    /// The petals, and the rect that puts the middle of them at the origin
+    pub fn petals(self) -> (Rect, &'static spritesheet::FrameRect) {
+        let (size, frame) = match self {
+            Sprite::Daisy => (
+                spritesheet::FLOWER_FRAME_SIZE,
+                &spritesheet::FLOWER_FRAMES[0],
+            ),
+            Sprite::Poppy => (spritesheet::POPPY_FRAME_SIZE, &spritesheet::POPPY_FRAMES[0]),
+        };
+        let rect = Rect {
+            x: -(size.x as f32 * 0.5),
+            y: -(size.y as f32 * 0.5),
+            w: size.x as f32,
+            h: size.y as f32,
+        };
+        (rect, frame)
+    }
+}
+
+/// A kind of flower
+#[derive(Clone, Debug, Deserialize)]
+pub struct Species {
+    pub name: String,
+    pub sprite: Sprite,
+    /// Scales [`Settings::flower_size`]
+    pub size: Range<f32>,
+    /// Red, green and blue. Empty for any flower color.
+    palette: Vec<(f32, f32, f32)>,
+    /// How much pollen one of these flowers is worth
+    pub nectar: u32,
+    /// How common the species is, next to the others
+    weight: f32,
+}
+impl Species {
+    /// A color from the palette, if there is one
+    pub fn rand_color(&self, rng: &mut Rng) -> Option<Color> {
+        if self.palette.is_empty() {
+            return None;
+        }
+        let n = (rng.gen_range(0., self.palette.len() as f32) as usize).min(self.palette.len() - 1);
+        let (r, g, b) = self.palette[n];
+        Some(Color::new(r, g, b, 1.))
+    }
+}
+
+/// Every species in the meadow. Loaded as a resource.
+#[derive(Clone, Debug)]
+pub struct Flora(Vec<Species>);
+impl Flora {
+    pub fn load() -> Self {
+        let species: Vec<Species> = ron::from_str(SPECIES_RON).expect("Bad species.ron");
+        assert!(!species.is_empty(), "No species in species.ron");
+        Flora(species)
+    }
+
+    pub fn get(&self, species: usize) -> &Species {
+        &self.0[species]
+    }
+
+    /// Is there a species at `species`? Flowers from an older save may
+    /// point past the end.
+    pub fn has(&self, species: usize) -> bool {
+        species < self.0.len()
+    }
+
+    /// A species, picked by weight
+    pub fn rand(&self, rng: &mut Rng) -> usize {
+        let total: f32 = self.0.iter().map(|species| species.weight).sum();
+        let mut pick = rng.gen_range(0., total);
+        for (n, species) in self.0.iter().enumerate() {
+            if pick < species.weight {
+                return n;
+            }
+            pick -= species.weight;
+        }
+        self.0.len() - 1
+    }
+
+    /// The most any species scales [`Settings::flower_size`] by
+    pub fn largest(&self) -> f32 {
+        self.0
+            .iter()
+            .map(|species| species.size.end)
+            .fold(0., f32::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn species(weight: f32) -> Species {
+        Species {
+            name: format!("Weighs {}", weight),
+            sprite: Sprite::Daisy,
+            size: 1.0..1.0,
+            palette: vec![],
+            nectar: 1,
+            weight,
+        }
+    }
+
+    #[test]
+    fn rand_picks_by_weight() {
+        let flora = Flora(vec![species(3.), species(0.), species(1.)]);
+        let mut rng = Rng::new(11);
+        let mut picked = [0; 3];
+        for _ in 0..4000 {
+            picked[flora.rand(&mut rng)] += 1;
+        }
+        assert_eq!(picked[1], 0, "picked a species that never grows");
+        // About three of the first for every one of the last
+        let ratio = picked[0] as f32 / picked[2] as f32;
+        assert!((2.5..3.5).contains(&ratio), "{:?}", picked);
+    }
+
+    #[test]
+    fn the_species_file_loads() {
+        let flora = Flora::load();
+        assert!(flora.has(0));
+        assert!(!flora.has(flora.0.len()));
+        assert!(flora
+            .0
+            .iter()
+            .all(|species| species.weight > 0. && species.nectar > 0));
+        assert!(flora.largest() >= 1.);
+    }
+}
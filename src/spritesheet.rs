@@ -17,29 +17,29 @@ pub const BEE_FLYING_FRAME_SIZE: XY<u32> = XY {
 };
 pub const BEE_FLYING_FRAME_UV: XY<f32> = XY {
     x: 0.25f32,
-    y: 0.3575419f32,
+    y: 0.1733243f32,
 };
 pub const BEE_FLYING_SPRITE_Y_OFFSET: u32 = 0u32;
 pub const BEE_FLYING_FRAMES: [FrameRect; 4] = [
     FrameRect {
         pixel_offset: XY { x: 0u32, y: 0u32 },
         xy: Rect { x: 0f32, y: 0f32, w: 163f32, h: 256f32 },
-        uv: Rect { x: 0f32, y: 0f32, w: 0.25f32, h: 0.3575419f32 },
+        uv: Rect { x: 0f32, y: 0f32, w: 0.25f32, h: 0.1733243f32 },
     },
     FrameRect {
         pixel_offset: XY { x: 163u32, y: 0u32 },
         xy: Rect { x: 163f32, y: 0f32, w: 163f32, h: 256f32 },
-        uv: Rect { x: 0.25f32, y: 0f32, w: 0.25f32, h: 0.3575419f32 },
+        uv: Rect { x: 0.25f32, y: 0f32, w: 0.25f32, h: 0.1733243f32 },
     },
     FrameRect {
         pixel_offset: XY { x: 326u32, y: 0u32 },
         xy: Rect { x: 326f32, y: 0f32, w: 163f32, h: 256f32 },
-        uv: Rect { x: 0.5f32, y: 0f32, w: 0.25f32, h: 0.3575419f32 },
+        uv: Rect { x: 0.5f32, y: 0f32, w: 0.25f32, h: 0.1733243f32 },
     },
     FrameRect {
         pixel_offset: XY { x: 489u32, y: 0u32 },
         xy: Rect { x: 489f32, y: 0f32, w: 163f32, h: 256f32 },
-        uv: Rect { x: 0.75f32, y: 0f32, w: 0.25f32, h: 0.3575419f32 },
+        uv: Rect { x: 0.75f32, y: 0f32, w: 0.25f32, h: 0.1733243f32 },
     },
 ];
 
@@ -50,14 +50,42 @@ pub const BEE_WALKING_FRAME_SIZE: XY<u32> = XY {
 };
 pub const BEE_WALKING_FRAME_UV: XY<f32> = XY {
     x: 0.39263803f32,
-    y: 0.2849162f32,
+    y: 0.1381178f32,
 };
 pub const BEE_WALKING_SPRITE_Y_OFFSET: u32 = 256u32;
 pub const BEE_WALKING_FRAMES: [FrameRect; 1] = [
     FrameRect {
         pixel_offset: XY { x: 0u32, y: 256u32 },
         xy: Rect { x: 0f32, y: 256f32, w: 256f32, h: 204f32 },
-        uv: Rect { x: 0f32, y: 0.3575419f32, w: 0.39263803f32, h: 0.2849162f32 },
+        uv: Rect { x: 0f32, y: 0.1733243f32, w: 0.39263803f32, h: 0.1381178f32 },
+    },
+];
+
+
+pub const BIRD_FRAME_SIZE: XY<u32> = XY {
+    x: 176u32,
+    y: 256u32,
+};
+pub const BIRD_FRAME_UV: XY<f32> = XY {
+    x: 0.26993865f32,
+    y: 0.1733243f32,
+};
+pub const BIRD_SPRITE_Y_OFFSET: u32 = 460u32;
+pub const BIRD_FRAMES: [FrameRect; 3] = [
+    FrameRect {
+        pixel_offset: XY { x: 0u32, y: 460u32 },
+        xy: Rect { x: 0f32, y: 460f32, w: 176f32, h: 256f32 },
+        uv: Rect { x: 0f32, y: 0.3114421f32, w: 0.26993865f32, h: 0.1733243f32 },
+    },
+    FrameRect {
+        pixel_offset: XY { x: 176u32, y: 460u32 },
+        xy: Rect { x: 176f32, y: 460f32, w: 176f32, h: 256f32 },
+        uv: Rect { x: 0.26993865f32, y: 0.3114421f32, w: 0.26993865f32, h: 0.1733243f32 },
+    },
+    FrameRect {
+        pixel_offset: XY { x: 352u32, y: 460u32 },
+        xy: Rect { x: 352f32, y: 460f32, w: 176f32, h: 256f32 },
+        uv: Rect { x: 0.5398773f32, y: 0.3114421f32, w: 0.26993865f32, h: 0.1733243f32 },
     },
 ];
 
@@ -68,19 +96,60 @@ pub const FLOWER_FRAME_SIZE: XY<u32> = XY {
 };
 pub const FLOWER_FRAME_UV: XY<f32> = XY {
     x: 0.37576687f32,
-    y: 0.3575419f32,
+    y: 0.1733243f32,
 };
-pub const FLOWER_SPRITE_Y_OFFSET: u32 = 460u32;
+pub const FLOWER_SPRITE_Y_OFFSET: u32 = 716u32;
 pub const FLOWER_FRAMES: [FrameRect; 2] = [
     FrameRect {
-        pixel_offset: XY { x: 0u32, y: 460u32 },
-        xy: Rect { x: 0f32, y: 460f32, w: 245f32, h: 256f32 },
-        uv: Rect { x: 0f32, y: 0.6424581f32, w: 0.37576687f32, h: 0.3575419f32 },
+        pixel_offset: XY { x: 0u32, y: 716u32 },
+        xy: Rect { x: 0f32, y: 716f32, w: 245f32, h: 256f32 },
+        uv: Rect { x: 0f32, y: 0.48476642f32, w: 0.37576687f32, h: 0.1733243f32 },
+    },
+    FrameRect {
+        pixel_offset: XY { x: 245u32, y: 716u32 },
+        xy: Rect { x: 245f32, y: 716f32, w: 245f32, h: 256f32 },
+        uv: Rect { x: 0.37576687f32, y: 0.48476642f32, w: 0.37576687f32, h: 0.1733243f32 },
     },
+];
+
+
+pub const FLYTRAP_FRAME_SIZE: XY<u32> = XY {
+    x: 216u32,
+    y: 256u32,
+};
+pub const FLYTRAP_FRAME_UV: XY<f32> = XY {
+    x: 0.33128834f32,
+    y: 0.1733243f32,
+};
+pub const FLYTRAP_SPRITE_Y_OFFSET: u32 = 972u32;
+pub const FLYTRAP_FRAMES: [FrameRect; 2] = [
+    FrameRect {
+        pixel_offset: XY { x: 0u32, y: 972u32 },
+        xy: Rect { x: 0f32, y: 972f32, w: 216f32, h: 256f32 },
+        uv: Rect { x: 0f32, y: 0.6580907f32, w: 0.33128834f32, h: 0.1733243f32 },
+    },
+    FrameRect {
+        pixel_offset: XY { x: 216u32, y: 972u32 },
+        xy: Rect { x: 216f32, y: 972f32, w: 216f32, h: 256f32 },
+        uv: Rect { x: 0.33128834f32, y: 0.6580907f32, w: 0.33128834f32, h: 0.1733243f32 },
+    },
+];
+
+
+pub const POPPY_FRAME_SIZE: XY<u32> = XY {
+    x: 256u32,
+    y: 249u32,
+};
+pub const POPPY_FRAME_UV: XY<f32> = XY {
+    x: 0.39263803f32,
+    y: 0.16858497f32,
+};
+pub const POPPY_SPRITE_Y_OFFSET: u32 = 1228u32;
+pub const POPPY_FRAMES: [FrameRect; 1] = [
     FrameRect {
-        pixel_offset: XY { x: 245u32, y: 460u32 },
-        xy: Rect { x: 245f32, y: 460f32, w: 245f32, h: 256f32 },
-        uv: Rect { x: 0.37576687f32, y: 0.6424581f32, w: 0.37576687f32, h: 0.3575419f32 },
+        pixel_offset: XY { x: 0u32, y: 1228u32 },
+        xy: Rect { x: 0f32, y: 1228f32, w: 256f32, h: 249f32 },
+        uv: Rect { x: 0f32, y: 0.83141506f32, w: 0.39263803f32, h: 0.16858497f32 },
     },
 ];
 
@@ -0,0 +1,74 @@
+//! Somewhere to keep things for later.
+//!
+//! Files on native, the browser's localStorage on the web. Everything is kept
+//! as text under a short key.
+
+pub use backend::{load, remove, save};
+
+#[cfg(not(target_family = "wasm"))]
+mod backend {
+    use std::{io, path::PathBuf};
+
+    fn path(key: &str) -> PathBuf {
+        PathBuf::from(format!("{}.ron", key))
+    }
+
+    pub fn load(key: &str) -> Option<String> {
+        std::fs::read_to_string(path(key)).ok()
+    }
+
+    pub fn save(key: &str, value: &str) -> io::Result<()> {
+        std::fs::write(path(key), value)
+    }
+
+    pub fn remove(key: &str) -> io::Result<()> {
+        match std::fs::remove_file(path(key)) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            res => res,
+        }
+    }
+}
+
+/// Talks to the plugin in html/storage.js
+#[cfg(target_family = "wasm")]
+mod backend {
+    use sapp_jsutils::{JsObject, JsObjectWeak};
+    use std::io;
+
+    /// Checked against the version in html/storage.js
+    #[no_mangle]
+    extern "C" fn storage_crate_version() -> u32 {
+        // 0.1.0
+        1 << 16
+    }
+
+    extern "C" {
+        fn storage_get(key: JsObjectWeak) -> JsObject;
+        fn storage_set(key: JsObjectWeak, value: JsObjectWeak);
+        fn storage_remove(key: JsObjectWeak);
+    }
+
+    pub fn load(key: &str) -> Option<String> {
+        let key = JsObject::string(key);
+        let value = unsafe { storage_get(key.weak()) };
+        if value.is_nil() {
+            return None;
+        }
+        let mut res = String::new();
+        value.to_string(&mut res);
+        Some(res)
+    }
+
+    pub fn save(key: &str, value: &str) -> io::Result<()> {
+        let key = JsObject::string(key);
+        let value = JsObject::string(value);
+        unsafe { storage_set(key.weak(), value.weak()) };
+        Ok(())
+    }
+
+    pub fn remove(key: &str) -> io::Result<()> {
+        let key = JsObject::string(key);
+        unsafe { storage_remove(key.weak()) };
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+//! The spider webs.
+//!
+//! Sticky, and somebody lives there.
+
+use crate::{bee::Bee, power_ups::Effects, prelude::*};
+use legion::{world::SubWorld, IntoQuery as _};
+use parry2d::{math::Isometry, query::intersection_test, shape::Ball};
+use serde::{Deserialize, Serialize};
+
+/// How many threads run out from the middle of a web
+const SPOKES: usize = 8;
+/// How many times the spider went round
+const RINGS: usize = 4;
+/// How big the spider is, next to its web
+const SPIDER_SCALE: f32 = 0.08;
+
+/// A web strung across the meadow, and the spider that spun it
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Web {
+    pub radius: f32,
+    /// Where the spider is, from the middle of the web
+    pub spider: Vec2,
+}
+impl Web {
+    pub fn new(radius: f32) -> Self {
+        Web {
+            radius,
+            spider: Vec2::ZERO,
+        }
+    }
+}
+
+pub fn roll_call(
+    _world: &mut legion::world::World,
+    systems: &mut legion::systems::Builder,
+    _resources: &mut legion::systems::Resources,
+) {
+    systems.add_system(snag_system());
+    systems.add_system(creep_system());
+}
+
+pub fn draw_call(
+    systems: &mut legion::systems::Builder,
+    _resources: &mut legion::systems::Resources,
+) {
+    systems.add_system(draw_system());
+}
+
+/// Any bee touching a web gets snagged in it, until it pulls itself free.
+#[system]
+#[read_component(Web)]
+#[read_component(Position)]
+#[read_component(Effects)]
+#[write_component(Bee)]
+fn snag(world: &mut SubWorld, #[resource] clock: &GameClock, #[resource] settings: &Settings) {
+    let webs: Vec<(Ball, Isometry<f32>)> = <(&Web, &Position)>::query()
+        .iter(world)
+        .map(|(web, pos)| {
+            (
+                Ball::new(web.radius),
+                Isometry::translation(pos.0.x, pos.0.y),
+            )
+        })
+        .collect();
+    let identity = Isometry::identity();
+    for (bee, pos, effects) in <(&mut Bee, &Position, &Effects)>::query().iter_mut(world) {
+        let bee_shape = bee
+            .hitbox(pos.0, effects.bee_size(clock, settings))
+            .polyline();
+        let snagged = webs.iter().any(|(web, isometry)| {
+            intersection_test(&identity, &bee_shape, isometry, web).unwrap()
+        });
+        bee.snag(snagged);
+    }
+}
+
+/// Spiders creep along their web toward the nearest snagged bee, and back to
+/// the middle once it gets away.
+#[system]
+#[write_component(Web)]
+#[read_component(Bee)]
+#[read_component(Position)]
+fn creep(world: &mut SubWorld, #[resource] clock: &GameClock, #[resource] settings: &Settings) {
+    let snagged: Vec<Vec2> = <(&Bee, &Position)>::query()
+        .iter(world)
+        .filter(|(bee, _)| bee.is_snagged())
+        .map(|(_, pos)| pos.0)
+        .collect();
+    for (web, pos) in <(&mut Web, &Position)>::query().iter_mut(world) {
+        let spider = pos.0 + web.spider;
+        let prey = snagged
+            .iter()
+            .filter(|bee| bee.distance(pos.0) <= web.radius)
+            .min_by(|a, b| a.distance(spider).total_cmp(&b.distance(spider)));
+        let target = prey.map_or(Vec2::ZERO, |prey| *prey - pos.0);
+        let step = settings.spider_speed * clock.tick.as_secs_f32();
+        let to_go = target - web.spider;
+        web.spider = if to_go.length() <= step {
+            target
+        } else {
+            (web.spider + to_go.normalize() * step).clamp_length_max(web.radius)
+        };
+    }
+}
+
+#[system(for_each)]
+fn draw(web: &Web, pos: &Position) {
+    let Position(pos) = *pos;
+    let thread = web.radius / 100.;
+    let silk = Color::new(1., 1., 1., 0.6);
+    let spoke = |n: usize| {
+        let angle = n as f32 * std::f32::consts::TAU / SPOKES as f32;
+        vec2(angle.cos(), angle.sin())
+    };
+    for n in 0..SPOKES {
+        let end = pos + spoke(n) * web.radius;
+        draw_line(pos.x, pos.y, end.x, end.y, thread, silk);
+        for ring in 1..=RINGS {
+            let radius = web.radius * ring as f32 / RINGS as f32;
+            let from = pos + spoke(n) * radius;
+            let to = pos + spoke(n + 1) * radius;
+            draw_line(from.x, from.y, to.x, to.y, thread, silk);
+        }
+    }
+    let spider = pos + web.spider;
+    let body = web.radius * SPIDER_SCALE;
+    for n in 0..SPOKES {
+        let leg = spider + spoke(n) * body * 2.;
+        draw_line(spider.x, spider.y, leg.x, leg.y, body / 4., BLACK);
+    }
+    draw_circle(spider.x, spider.y, body, BLACK);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bee::Waypoints;
+    use legion::{Entity, EntityStore as _, Resources, Schedule, World};
+
+    fn bee_at(world: &mut World, pos: Vec2) -> Entity {
+        crate::bee::spawn(
+            world,
+            Bee::new(pos, 1.),
+            Position::from(pos),
+            Velocity::default(),
+            Waypoints::default(),
+        )
+    }
+
+    #[test]
+    fn spiders_creep_toward_snagged_bees() {
+        let mut world = World::default();
+        let web = world.push((Web::new(150.), Position::from(Vec2::ZERO)));
+        let snagged = bee_at(&mut world, vec2(100., 0.));
+        let free = bee_at(&mut world, vec2(0., 500.));
+        let mut resources = Resources::default();
+        resources.insert(Settings {
+            spider_speed: 40.,
+            ..Settings::default()
+        });
+        resources.insert(GameClock {
+            tick: Duration::from_secs(1),
+            ..GameClock::default()
+        });
+        let mut schedule = Schedule::builder()
+            .add_system(snag_system())
+            .flush()
+            .add_system(creep_system())
+            .build();
+        let is_snagged = |world: &World, bee| {
+            world
+                .entry_ref(bee)
+                .expect("bee missing")
+                .get_component::<Bee>()
+                .expect("bee missing bee data")
+                .is_snagged()
+        };
+        let spider = |world: &World| {
+            world
+                .entry_ref(web)
+                .expect("web missing")
+                .get_component::<Web>()
+                .expect("web missing web data")
+                .spider
+        };
+        schedule.execute(&mut world, &mut resources);
+        assert!(is_snagged(&world, snagged));
+        assert!(!is_snagged(&world, free));
+        assert_eq!(spider(&world), vec2(40., 0.));
+        for _ in 0..5 {
+            schedule.execute(&mut world, &mut resources);
+        }
+        // Right up to the bee, and no further
+        assert_eq!(spider(&world), vec2(100., 0.));
+    }
+}
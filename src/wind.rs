@@ -0,0 +1,113 @@
+//! The wind.
+//!
+//! It comes in gusts, and dies away again. Bees get blown about, and the
+//! flowers nod along.
+
+use std::f32::consts::TAU;
+
+use crate::prelude::*;
+
+/// How many waves of wind roll over the meadow at once
+const GUSTS: usize = 3;
+/// How far apart the calm patches are
+const CALM_SPACING: f32 = 1500.;
+/// How fast the calm patches drift, in radians per second
+const CALM_DRIFT: f32 = 0.05;
+
+/// A wave of wind rolling across the meadow
+#[derive(Clone, Copy, Debug)]
+struct Gust {
+    direction: Vec2,
+    wavelength: f32,
+    /// How fast it rolls along, in radians per second
+    speed: f32,
+    phase: f32,
+}
+
+/// The wind over the whole meadow, at any time
+#[derive(Clone, Debug)]
+pub struct Wind {
+    gusts: [Gust; GUSTS],
+    calm_phase: Vec2,
+}
+impl Wind {
+    pub fn new(rng: &mut Rng) -> Self {
+        // The gusts mostly come from the same way, give or take
+        let prevailing = rng.gen_range(0., TAU);
+        Wind {
+            gusts: array_init::array_init(|_| {
+                let angle = prevailing + rng.gen_range(-0.6, 0.6);
+                Gust {
+                    direction: vec2(angle.cos(), angle.sin()),
+                    wavelength: rng.gen_range(300., 1200.),
+                    speed: rng.gen_range(0.5, 2.),
+                    phase: rng.gen_range(0., TAU),
+                }
+            }),
+            calm_phase: vec2(rng.gen_range(0., TAU), rng.gen_range(0., TAU)),
+        }
+    }
+
+    /// Which way and how hard the wind is blowing at `pos`
+    pub fn at(&self, pos: Vec2, clock: &GameClock, settings: &Settings) -> Vec2 {
+        let time = clock.time as f32;
+        let gusts = self.gusts.iter().fold(Vec2::ZERO, |sum, gust| {
+            let wave =
+                pos.dot(gust.direction) / gust.wavelength * TAU - time * gust.speed + gust.phase;
+            sum + gust.direction * (0.5 + 0.5 * wave.sin())
+        });
+        let calm = pos / CALM_SPACING * TAU + self.calm_phase + Vec2::splat(time * CALM_DRIFT);
+        let calm = (calm.x.sin() * calm.y.sin()).abs();
+        gusts / GUSTS as f32 * calm * settings.wind_strength
+    }
+}
+
+pub fn roll_call(
+    _world: &mut legion::world::World,
+    _systems: &mut legion::systems::Builder,
+    resources: &mut legion::systems::Resources,
+) {
+    let wind = {
+        let mut rng = resources.get_mut::<Rng>().expect("No rng");
+        Wind::new(&mut rng)
+    };
+    resources.insert(wind);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn never_blows_harder_than_the_strongest_gust() {
+        let settings = Settings::default();
+        let wind = Wind::new(&mut Rng::new(8));
+        let mut rng = Rng::new(9);
+        let mut felt = 0f32;
+        for _ in 0..2000 {
+            let pos = vec2(rng.gen_range(-5000., 5000.), rng.gen_range(-5000., 5000.));
+            let clock = GameClock {
+                time: rng.gen_range(0., 600.) as f64,
+                ..GameClock::default()
+            };
+            let strength = wind.at(pos, &clock, &settings).length();
+            assert!(strength <= settings.wind_strength + 1e-3);
+            felt = felt.max(strength);
+        }
+        assert!(felt > 0., "never a breath of wind");
+    }
+
+    #[test]
+    fn still_air_is_still() {
+        let settings = Settings {
+            wind_strength: 0.,
+            ..Settings::default()
+        };
+        let wind = Wind::new(&mut Rng::new(8));
+        let clock = GameClock {
+            time: 12.,
+            ..GameClock::default()
+        };
+        assert_eq!(wind.at(vec2(300., 400.), &clock, &settings), Vec2::ZERO);
+    }
+}